@@ -0,0 +1,82 @@
+/**
+ * Acceptance rate bookkeeping: an all-time rate plus a rate over a sliding
+ * window of the most recent proposals.
+ */
+use std::collections::VecDeque;
+
+pub const DEFAULT_WINDOW: usize = 50;
+
+#[derive(Debug, Clone)]
+pub struct AcceptanceRate {
+    accepted: usize,
+    total: usize,
+    window_size: usize,
+    window: VecDeque<bool>,
+    window_accepted: usize,
+}
+
+impl Default for AcceptanceRate {
+    fn default() -> Self {
+        AcceptanceRate::new(DEFAULT_WINDOW)
+    }
+}
+
+impl AcceptanceRate {
+    pub fn new(window_size: usize) -> Self {
+        AcceptanceRate {
+            accepted: 0,
+            total: 0,
+            window_size: window_size.max(1),
+            window: VecDeque::with_capacity(window_size),
+            window_accepted: 0,
+        }
+    }
+
+    pub fn record(&mut self, accepted: bool) {
+        self.total += 1;
+        if accepted {
+            self.accepted += 1;
+            self.window_accepted += 1;
+        }
+        self.window.push_back(accepted);
+        if self.window.len() > self.window_size && self.window.pop_front() == Some(true) {
+            self.window_accepted -= 1;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = AcceptanceRate::new(self.window_size);
+    }
+
+    pub fn accepted(&self) -> usize {
+        self.accepted
+    }
+
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// All-time acceptance rate, `None` before the first proposal.
+    pub fn overall(&self) -> Option<f64> {
+        if self.total == 0 {
+            return None;
+        }
+        Some(self.accepted as f64 / self.total as f64)
+    }
+
+    /// Acceptance rate over the last `window_size` proposals.
+    pub fn recent(&self) -> Option<f64> {
+        if self.window.is_empty() {
+            return None;
+        }
+        Some(self.window_accepted as f64 / self.window.len() as f64)
+    }
+
+    pub fn window_accepted(&self) -> usize {
+        self.window_accepted
+    }
+
+    pub fn window_len(&self) -> usize {
+        self.window.len()
+    }
+}
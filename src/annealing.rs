@@ -0,0 +1,85 @@
+/**
+ * Simulated annealing on top of the Metropolis sampler. The target is raised
+ * to an inverse temperature beta = 1 / T which grows as T decays, so the
+ * chains concentrate on the mode instead of sampling the whole density.
+ */
+use iced::Point;
+use std::fmt;
+
+const MIN_TEMPERATURE: f64 = 1e-3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Schedule {
+    Linear,
+    Geometric,
+    Logarithmic,
+}
+
+impl Schedule {
+    pub const ALL: [Schedule; 3] = [Schedule::Linear, Schedule::Geometric, Schedule::Logarithmic];
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Schedule::Linear => "Linear",
+                Schedule::Geometric => "Geometric",
+                Schedule::Logarithmic => "Logarithmic",
+            }
+        )
+    }
+}
+
+pub struct Annealer {
+    pub schedule: Schedule,
+    pub initial_temperature: f64,
+    // number of steps the linear schedule takes to cool down.
+    pub steps: usize,
+    // multiplicative decay per step of the geometric schedule.
+    pub cooling_rate: f64,
+    pub iteration: usize,
+    // best point seen so far and its density.
+    pub best: Option<(Point, f64)>,
+}
+
+impl Annealer {
+    pub fn new(schedule: Schedule, initial_temperature: f64) -> Self {
+        Annealer {
+            schedule,
+            initial_temperature,
+            steps: 500,
+            cooling_rate: 0.99,
+            iteration: 0,
+            best: None,
+        }
+    }
+
+    pub fn temperature(&self) -> f64 {
+        let t0 = self.initial_temperature;
+        let k = self.iteration as f64;
+        let t = match self.schedule {
+            Schedule::Linear => t0 * (1.0 - k / self.steps as f64),
+            Schedule::Geometric => t0 * self.cooling_rate.powf(k),
+            Schedule::Logarithmic => t0 / (k + std::f64::consts::E).ln(),
+        };
+        f64::max(t, MIN_TEMPERATURE)
+    }
+
+    pub fn beta(&self) -> f64 {
+        1.0 / self.temperature()
+    }
+
+    pub fn advance(&mut self) {
+        self.iteration += 1;
+    }
+
+    pub fn observe(&mut self, position: Point, density: f64) {
+        match self.best {
+            Some((_, best)) if best >= density => {}
+            _ => self.best = Some((position, density)),
+        }
+    }
+}
@@ -1,12 +1,19 @@
 use crate::gaussian;
+use crate::viewport::Viewport;
 
 use iced::widget::canvas::{self, stroke, Cache, Stroke};
 use iced::{Color, Point, Theme};
 
+// height of the curve, in model units per unit of density.
+const CURVE_HEIGHT: f32 = 0.4;
+const CURVE_POINTS: usize = 200;
+
 pub struct BellCurve {
     mu: f64,
     sigma: f64,
-    pub position: Point,
+    // model x coordinate of the dot travelling along the curve.
+    pub position: f32,
+    pub viewport: Viewport,
     curve_cache: Cache,
     position_cache: Cache,
 }
@@ -15,7 +22,8 @@ impl Default for BellCurve {
         Self {
             mu: 2.0,
             sigma: 0.2,
-            position: Point { x: 0.0, y: 0.0 },
+            position: 0.0,
+            viewport: Viewport::default(),
             curve_cache: canvas::Cache::default(),
             position_cache: canvas::Cache::default(),
         }
@@ -26,7 +34,8 @@ impl BellCurve {
         Self {
             mu,
             sigma,
-            position: Point { x: 0.0, y: 0.0 },
+            position: 0.0,
+            viewport: Viewport::default(),
             curve_cache: canvas::Cache::default(),
             position_cache: canvas::Cache::default(),
         }
@@ -51,35 +60,33 @@ impl<Message> canvas::Program<Message> for BellCurve {
         bounds: iced::Rectangle,
         _cursor: canvas::Cursor,
     ) -> Vec<canvas::Geometry> {
-        let geom = self.curve_cache.draw(bounds.size(), |frame| {
-            //let divisor = 50.0 / self.mu as f32;
-            let divisor = 50.0 / 2.0;
-            let mut current_point = Point { x: 0.0, y: 0.0 };
-            for i in 0..100 {
-                let i = i as f32;
-                let next_point = Point {
-                    x: i * 10.0,
-                    y: self.value_at((i as f64) / (divisor)) as f32 * 100.0,
-                };
-                frame.stroke(
-                    &canvas::Path::new(|path| {
-                        path.move_to(current_point);
-                        path.line_to(Point {
-                            x: i * 10.0,
-                            y: self.value_at((i as f64) / divisor) as f32 * 100.0,
-                        });
-                    }),
-                    canvas::Stroke {
-                        width: 5.0,
-                        style: stroke::Style::Solid(Color::WHITE),
-                        ..Stroke::default()
-                    },
-                );
-                current_point = next_point
-            }
+        let size = bounds.size();
+        let view = self.viewport;
+        let scale = view.scale(size);
+        let (visible_min, visible_max) = view.visible(size);
+        // the curve stands on the bottom edge of the canvas.
+        let to_screen = |x: f32| Point {
+            x: view.to_screen(Point::new(x, 0.0), size).x,
+            y: size.height - self.value_at(x as f64) as f32 * CURVE_HEIGHT * scale,
+        };
+        let geom = self.curve_cache.draw(size, |frame| {
+            let step = (visible_max.x - visible_min.x) / CURVE_POINTS as f32;
+            frame.stroke(
+                &canvas::Path::new(|path| {
+                    path.move_to(to_screen(visible_min.x));
+                    for i in 1..=CURVE_POINTS {
+                        path.line_to(to_screen(visible_min.x + i as f32 * step));
+                    }
+                }),
+                canvas::Stroke {
+                    width: 5.0,
+                    style: stroke::Style::Solid(Color::WHITE),
+                    ..Stroke::default()
+                },
+            );
         });
-        let pos = self.position_cache.draw(bounds.size(), |frame| {
-            let path = canvas::Path::circle(self.position, 5.0);
+        let pos = self.position_cache.draw(size, |frame| {
+            let path = canvas::Path::circle(to_screen(self.position), 5.0);
             frame.fill(&path, Color::from_rgb8(0x12, 0x93, 0xD8));
         });
         vec![geom, pos]
@@ -0,0 +1,27 @@
+/**
+ * Colour maps for the density layers drawn on the stage.
+ */
+use iced::Color;
+
+// samples of matplotlib's viridis at t = 0, 0.25, 0.5, 0.75, 1.
+const VIRIDIS: [(f32, f32, f32); 5] = [
+    (0.267, 0.005, 0.329),
+    (0.229, 0.322, 0.546),
+    (0.128, 0.567, 0.551),
+    (0.369, 0.789, 0.383),
+    (0.993, 0.906, 0.144),
+];
+
+/// Viridis colour for `t` in [0, 1], linearly interpolated.
+pub fn viridis(t: f32, alpha: f32) -> Color {
+    let t = t.clamp(0.0, 1.0) * (VIRIDIS.len() - 1) as f32;
+    let i = (t as usize).min(VIRIDIS.len() - 2);
+    let f = t - i as f32;
+    let (a, b) = (VIRIDIS[i], VIRIDIS[i + 1]);
+    Color::from_rgba(
+        a.0 + (b.0 - a.0) * f,
+        a.1 + (b.1 - a.1) * f,
+        a.2 + (b.2 - a.2) * f,
+        alpha,
+    )
+}
@@ -0,0 +1,124 @@
+/**
+ * Contours of densities sampled on a regular grid.
+ */
+use iced::Point;
+
+/// Density values at the nodes of a regular grid, row by row: the value at
+/// column `i` and row `j` is `values[j * columns + i]`.
+pub struct Grid {
+    pub columns: usize,
+    pub rows: usize,
+    pub values: Vec<f64>,
+}
+
+impl Grid {
+    /// Evaluates `density` on `columns` x `rows` nodes spread evenly over the
+    /// rectangle from `min` to `max`.
+    pub fn sample(
+        density: impl Fn(Point) -> f64,
+        min: Point,
+        max: Point,
+        columns: usize,
+        rows: usize,
+    ) -> Self {
+        let step_x = (max.x - min.x) / (columns - 1).max(1) as f32;
+        let step_y = (max.y - min.y) / (rows - 1).max(1) as f32;
+        let values = (0..rows)
+            .flat_map(|j| (0..columns).map(move |i| (i, j)))
+            .map(|(i, j)| {
+                density(Point::new(
+                    min.x + i as f32 * step_x,
+                    min.y + j as f32 * step_y,
+                ))
+            })
+            .collect();
+        Self {
+            columns,
+            rows,
+            values,
+        }
+    }
+
+    pub fn value(&self, i: usize, j: usize) -> f64 {
+        self.values[j * self.columns + i]
+    }
+
+    pub fn max(&self) -> f64 {
+        self.values.iter().cloned().fold(0.0, f64::max)
+    }
+}
+
+/// Density thresholds of the highest posterior density regions holding each
+/// of `probabilities` of the mass, treating every grid node as an equal-area
+/// cell.
+pub fn hpd_levels(grid: &Grid, probabilities: &[f64]) -> Vec<f64> {
+    let mut sorted = grid.values.clone();
+    sorted.sort_by(|a, b| b.total_cmp(a));
+    let total: f64 = sorted.iter().sum();
+    let mut cumulative = Vec::with_capacity(sorted.len());
+    let mut sum = 0.0;
+    for value in sorted.iter() {
+        sum += value;
+        cumulative.push(sum);
+    }
+    probabilities
+        .iter()
+        .map(|p| {
+            let index = cumulative.partition_point(|c| *c < p * total);
+            sorted[index.min(sorted.len() - 1)]
+        })
+        .collect()
+}
+
+/// Line segments where the grid crosses `level`, found with marching squares.
+/// Endpoints are in fractional grid coordinates (column, row).
+pub fn marching_squares(grid: &Grid, level: f64) -> Vec<(Point, Point)> {
+    let mut segments = Vec::new();
+    for j in 0..grid.rows.saturating_sub(1) {
+        for i in 0..grid.columns.saturating_sub(1) {
+            // corners counter-clockwise from the bottom left.
+            let corners = [
+                grid.value(i, j),
+                grid.value(i + 1, j),
+                grid.value(i + 1, j + 1),
+                grid.value(i, j + 1),
+            ];
+            let inside = corners.map(|v| v >= level);
+            // where each edge (bottom, right, top, left) crosses the level.
+            let crossing = |a: usize, b: usize, from: (f32, f32), to: (f32, f32)| {
+                (inside[a] != inside[b]).then(|| {
+                    let t = ((level - corners[a]) / (corners[b] - corners[a])) as f32;
+                    Point::new(
+                        i as f32 + from.0 + (to.0 - from.0) * t,
+                        j as f32 + from.1 + (to.1 - from.1) * t,
+                    )
+                })
+            };
+            let bottom = crossing(0, 1, (0.0, 0.0), (1.0, 0.0));
+            let right = crossing(1, 2, (1.0, 0.0), (1.0, 1.0));
+            let top = crossing(3, 2, (0.0, 1.0), (1.0, 1.0));
+            let left = crossing(0, 3, (0.0, 0.0), (0.0, 1.0));
+            match (bottom, right, top, left) {
+                (Some(b), Some(r), Some(t), Some(l)) => {
+                    // saddle: the centre decides which corners are cut off.
+                    let centre = corners.iter().sum::<f64>() / 4.0 >= level;
+                    if centre == inside[0] {
+                        segments.push((b, r));
+                        segments.push((t, l));
+                    } else {
+                        segments.push((l, b));
+                        segments.push((r, t));
+                    }
+                }
+                _ => {
+                    let crossed: Vec<Point> =
+                        [bottom, right, top, left].into_iter().flatten().collect();
+                    if let [a, b] = crossed[..] {
+                        segments.push((a, b));
+                    }
+                }
+            }
+        }
+    }
+    segments
+}
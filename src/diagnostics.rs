@@ -0,0 +1,265 @@
+/**
+ * Convergence and efficiency diagnostics computed from the stored chains.
+ * Multi-chain estimators follow Vehtari et al. (2021), "Rank-normalization,
+ * folding, and localization: An improved R-hat for assessing convergence of
+ * MCMC". Every function expects chains of equal length.
+ */
+use crate::gaussian;
+
+/// Sample autocorrelation of `x` for lags 0..=max_lag.
+pub fn autocorrelation(x: &[f64], max_lag: usize) -> Vec<f64> {
+    let n = x.len();
+    if n < 2 {
+        return Vec::new();
+    }
+    let acov = autocovariance(x, max_lag.min(n - 1));
+    if acov[0] == 0.0 {
+        return vec![1.0; acov.len()];
+    }
+    acov.iter().map(|c| c / acov[0]).collect()
+}
+
+/// Biased (divide by n) autocovariance for lags 0..=max_lag.
+pub fn autocovariance(x: &[f64], max_lag: usize) -> Vec<f64> {
+    let n = x.len();
+    let mean = x.iter().sum::<f64>() / n as f64;
+    let centered: Vec<f64> = x.iter().map(|v| v - mean).collect();
+    (0..=max_lag.min(n.saturating_sub(1)))
+        .map(|lag| {
+            centered[..n - lag]
+                .iter()
+                .zip(centered[lag..].iter())
+                .map(|(a, b)| a * b)
+                .sum::<f64>()
+                / n as f64
+        })
+        .collect()
+}
+
+/// Autocorrelation averaged over chains, lags 0..=max_lag.
+pub fn mean_autocorrelation(chains: &[Vec<f64>], max_lag: usize) -> Vec<f64> {
+    let acfs: Vec<Vec<f64>> = chains
+        .iter()
+        .map(|chain| autocorrelation(chain, max_lag))
+        .filter(|acf| !acf.is_empty())
+        .collect();
+    if acfs.is_empty() {
+        return Vec::new();
+    }
+    let lags = acfs.iter().map(|acf| acf.len()).min().unwrap_or(0);
+    (0..lags)
+        .map(|lag| acfs.iter().map(|acf| acf[lag]).sum::<f64>() / acfs.len() as f64)
+        .collect()
+}
+
+fn mean(x: &[f64]) -> f64 {
+    x.iter().sum::<f64>() / x.len() as f64
+}
+
+fn variance(x: &[f64]) -> f64 {
+    let m = mean(x);
+    x.iter().map(|v| (v - m) * (v - m)).sum::<f64>() / (x.len() as f64 - 1.0)
+}
+
+/// Splits every chain in half, doubling the number of chains.
+pub fn split_chains(chains: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    chains
+        .iter()
+        .flat_map(|chain| {
+            let half = chain.len() / 2;
+            vec![chain[..half].to_vec(), chain[chain.len() - half..].to_vec()]
+        })
+        .collect()
+}
+
+/// Replaces each draw by the normal score of its rank in the pooled draws.
+pub fn rank_normalize(chains: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let mut pooled: Vec<(f64, usize, usize)> = chains
+        .iter()
+        .enumerate()
+        .flat_map(|(c, chain)| chain.iter().enumerate().map(move |(i, v)| (*v, c, i)))
+        .collect();
+    pooled.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let total = pooled.len() as f64;
+    let mut normalized: Vec<Vec<f64>> = chains.iter().map(|c| vec![0.0; c.len()]).collect();
+
+    // ties share their average rank.
+    let mut start = 0;
+    while start < pooled.len() {
+        let mut end = start;
+        while end + 1 < pooled.len() && pooled[end + 1].0 == pooled[start].0 {
+            end += 1;
+        }
+        let rank = (start + end) as f64 / 2.0 + 1.0;
+        let z = gaussian::inverse_cdf((rank - 0.375) / (total + 0.25));
+        for &(_, c, i) in pooled[start..=end].iter() {
+            normalized[c][i] = z;
+        }
+        start = end + 1;
+    }
+    normalized
+}
+
+/// Effective sample size of the pooled chains using Geyer's initial monotone
+/// sequence estimator. `None` with fewer than 4 draws per chain or when the
+/// draws have no variance.
+pub fn ess(chains: &[Vec<f64>]) -> Option<f64> {
+    let m = chains.len();
+    let n = chains.iter().map(|chain| chain.len()).min()?;
+    if n < 4 {
+        return None;
+    }
+    let chains: Vec<&[f64]> = chains.iter().map(|chain| &chain[..n]).collect();
+
+    let means: Vec<f64> = chains.iter().map(|chain| mean(chain)).collect();
+    let centered: Vec<Vec<f64>> = chains
+        .iter()
+        .zip(means.iter())
+        .map(|(chain, mean)| chain.iter().map(|v| v - mean).collect())
+        .collect();
+    let within = chains.iter().map(|chain| variance(chain)).sum::<f64>() / m as f64;
+    let between = if m > 1 {
+        n as f64 * variance(&means)
+    } else {
+        0.0
+    };
+    let var_plus = (n as f64 - 1.0) / n as f64 * within + between / n as f64;
+    if var_plus <= 0.0 || !var_plus.is_finite() {
+        return None;
+    }
+
+    // lags are computed on demand since the sum usually stops early.
+    let rho = |t: usize| {
+        let mean_acov = centered
+            .iter()
+            .map(|chain| {
+                chain[..n - t]
+                    .iter()
+                    .zip(chain[t..].iter())
+                    .map(|(a, b)| a * b)
+                    .sum::<f64>()
+                    / n as f64
+            })
+            .sum::<f64>()
+            / m as f64;
+        1.0 - (within - mean_acov) / var_plus
+    };
+
+    // sum consecutive pairs while positive, forcing the pair sums to be monotone.
+    let mut tau = 0.0;
+    let mut previous = f64::INFINITY;
+    let mut t = 0;
+    while t + 1 < n {
+        let pair = rho(t) + rho(t + 1);
+        if pair <= 0.0 {
+            break;
+        }
+        let pair = pair.min(previous);
+        tau += pair;
+        previous = pair;
+        t += 2;
+    }
+    let tau = (2.0 * tau - 1.0).max(1.0 / (m as f64 * n as f64).log10());
+    Some(m as f64 * n as f64 / tau)
+}
+
+/// ESS of the rank normalized split chains, for the centre of the distribution.
+pub fn bulk_ess(chains: &[Vec<f64>]) -> Option<f64> {
+    ess(&rank_normalize(&split_chains(chains)))
+}
+
+/// Minimum ESS of the 5% and 95% quantile indicators, for the tails.
+pub fn tail_ess(chains: &[Vec<f64>]) -> Option<f64> {
+    let mut pooled: Vec<f64> = chains.iter().flatten().cloned().collect();
+    if pooled.is_empty() {
+        return None;
+    }
+    pooled.sort_by(|a, b| a.total_cmp(b));
+    let quantile = |q: f64| pooled[((pooled.len() - 1) as f64 * q).round() as usize];
+    let (lower, upper) = (quantile(0.05), quantile(0.95));
+    let indicator = |f: &dyn Fn(f64) -> bool| -> Vec<Vec<f64>> {
+        chains
+            .iter()
+            .map(|chain| chain.iter().map(|v| f(*v) as u8 as f64).collect())
+            .collect()
+    };
+    let low = ess(&split_chains(&indicator(&|v| v <= lower)))?;
+    let high = ess(&split_chains(&indicator(&|v| v >= upper)))?;
+    Some(low.min(high))
+}
+
+/// Potential scale reduction factor of the given chains, sqrt(var_plus / W).
+pub fn rhat(chains: &[Vec<f64>]) -> Option<f64> {
+    let m = chains.len();
+    let n = chains.iter().map(|chain| chain.len()).min()?;
+    if m < 2 || n < 2 {
+        return None;
+    }
+    let chains: Vec<&[f64]> = chains.iter().map(|chain| &chain[..n]).collect();
+    let means: Vec<f64> = chains.iter().map(|chain| mean(chain)).collect();
+    let within = chains.iter().map(|chain| variance(chain)).sum::<f64>() / m as f64;
+    let between = n as f64 * variance(&means);
+    if within <= 0.0 || !within.is_finite() {
+        return None;
+    }
+    let var_plus = (n as f64 - 1.0) / n as f64 * within + between / n as f64;
+    Some((var_plus / within).sqrt())
+}
+
+/// R-hat of the chains split in half, which also catches within-chain trends.
+pub fn split_rhat(chains: &[Vec<f64>]) -> Option<f64> {
+    rhat(&split_chains(chains))
+}
+
+/// Rank normalized split R-hat: the larger of the R-hat of the rank normalized
+/// draws (location) and of the rank normalized folded draws (scale).
+pub fn rank_rhat(chains: &[Vec<f64>]) -> Option<f64> {
+    let split = split_chains(chains);
+    let mut pooled: Vec<f64> = split.iter().flatten().cloned().collect();
+    if pooled.is_empty() {
+        return None;
+    }
+    pooled.sort_by(|a, b| a.total_cmp(b));
+    let median = pooled[pooled.len() / 2];
+    let folded: Vec<Vec<f64>> = split
+        .iter()
+        .map(|chain| chain.iter().map(|v| (v - median).abs()).collect())
+        .collect();
+    let bulk = rhat(&rank_normalize(&split))?;
+    let tail = rhat(&rank_normalize(&folded))?;
+    Some(bulk.max(tail))
+}
+
+/// Per-coordinate diagnostics of the stage population.
+#[derive(Debug, Clone, Default)]
+pub struct ChainSummary {
+    pub bulk_ess: (Option<f64>, Option<f64>),
+    pub tail_ess: (Option<f64>, Option<f64>),
+    pub acf: (Vec<f64>, Vec<f64>),
+    pub split_rhat: (Option<f64>, Option<f64>),
+    pub rank_rhat: (Option<f64>, Option<f64>),
+}
+
+impl ChainSummary {
+    /// True once the rank normalized R-hat of both coordinates is below
+    /// `threshold`.
+    pub fn converged(&self, threshold: f64) -> bool {
+        match self.rank_rhat {
+            (Some(x), Some(y)) => x < threshold && y < threshold,
+            _ => false,
+        }
+    }
+}
+
+pub fn summarize(xs: &[Vec<f64>], ys: &[Vec<f64>], max_lag: usize) -> ChainSummary {
+    ChainSummary {
+        bulk_ess: (bulk_ess(xs), bulk_ess(ys)),
+        tail_ess: (tail_ess(xs), tail_ess(ys)),
+        acf: (
+            mean_autocorrelation(xs, max_lag),
+            mean_autocorrelation(ys, max_lag),
+        ),
+        split_rhat: (split_rhat(xs), split_rhat(ys)),
+        rank_rhat: (rank_rhat(xs), rank_rhat(ys)),
+    }
+}
@@ -0,0 +1,222 @@
+/**
+ * Posterior expectations estimated from the stored chains, with their Monte
+ * Carlo standard error (MCSE). The MCSE is either estimated from the
+ * effective sample size, sd / sqrt(ESS), or from non-overlapping batch means
+ * within each chain.
+ */
+use crate::diagnostics;
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Coordinate {
+    X,
+    Y,
+}
+
+impl Coordinate {
+    pub const ALL: [Coordinate; 2] = [Coordinate::X, Coordinate::Y];
+}
+
+impl fmt::Display for Coordinate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Coordinate::X => write!(f, "x"),
+            Coordinate::Y => write!(f, "y"),
+        }
+    }
+}
+
+/// Axis aligned rectangle, bounds may be infinite.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Region {
+    pub min: (f64, f64),
+    pub max: (f64, f64),
+}
+
+impl Region {
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        (self.min.0..=self.max.0).contains(&x) && (self.min.1..=self.max.1).contains(&y)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Functional {
+    Mean(Coordinate),
+    Variance(Coordinate),
+    // quantile level in (0, 1).
+    Quantile(Coordinate, f64),
+    Probability(Region),
+}
+
+impl fmt::Display for Functional {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Functional::Mean(c) => write!(f, "E[{}]", c),
+            Functional::Variance(c) => write!(f, "Var[{}]", c),
+            Functional::Quantile(c, q) => write!(f, "q{:.2}[{}]", q, c),
+            Functional::Probability(region) => {
+                let bound =
+                    |name: &str, min: f64, max: f64| match (min.is_finite(), max.is_finite()) {
+                        (true, true) => format!("{:.2} <= {} <= {:.2}", min, name, max),
+                        (true, false) => format!("{} >= {:.2}", name, min),
+                        (false, true) => format!("{} <= {:.2}", name, max),
+                        (false, false) => String::new(),
+                    };
+                let bounds: Vec<String> = [
+                    bound("x", region.min.0, region.max.0),
+                    bound("y", region.min.1, region.max.1),
+                ]
+                .into_iter()
+                .filter(|b| !b.is_empty())
+                .collect();
+                write!(f, "P({})", bounds.join(", "))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum McseMethod {
+    BatchMeans,
+    Ess,
+}
+
+impl McseMethod {
+    pub const ALL: [McseMethod; 2] = [McseMethod::BatchMeans, McseMethod::Ess];
+}
+
+impl fmt::Display for McseMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            McseMethod::BatchMeans => write!(f, "Batch means"),
+            McseMethod::Ess => write!(f, "ESS"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    pub value: f64,
+    pub mcse: f64,
+}
+
+fn mean(chains: &[Vec<f64>]) -> f64 {
+    let n: usize = chains.iter().map(|chain| chain.len()).sum();
+    chains.iter().flatten().sum::<f64>() / n as f64
+}
+
+fn map(chains: &[Vec<f64>], f: impl Fn(f64) -> f64) -> Vec<Vec<f64>> {
+    chains
+        .iter()
+        .map(|chain| chain.iter().map(|v| f(*v)).collect())
+        .collect()
+}
+
+/// Standard error of the pooled mean of `chains`.
+pub fn mcse(chains: &[Vec<f64>], method: McseMethod) -> Option<f64> {
+    let total: usize = chains.iter().map(|chain| chain.len()).sum();
+    if total < 4 {
+        return None;
+    }
+    let m = mean(chains);
+    match method {
+        McseMethod::Ess => {
+            let var = chains
+                .iter()
+                .flatten()
+                .map(|v| (v - m) * (v - m))
+                .sum::<f64>()
+                / (total as f64 - 1.0);
+            let ess = diagnostics::ess(chains)?;
+            Some((var / ess).sqrt())
+        }
+        McseMethod::BatchMeans => {
+            let n = chains.iter().map(|chain| chain.len()).min()?;
+            let size = ((n as f64).sqrt() as usize).max(1);
+            let batches: Vec<f64> = chains
+                .iter()
+                .flat_map(|chain| {
+                    chain[..n - n % size]
+                        .chunks(size)
+                        .map(|batch| batch.iter().sum::<f64>() / size as f64)
+                })
+                .collect();
+            if batches.len() < 2 {
+                return None;
+            }
+            let var = batches.iter().map(|b| (b - m) * (b - m)).sum::<f64>()
+                / (batches.len() as f64 - 1.0);
+            Some((var / batches.len() as f64).sqrt())
+        }
+    }
+}
+
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let q = q.clamp(0.0, 1.0);
+    sorted[((sorted.len() - 1) as f64 * q).round() as usize]
+}
+
+/// Estimate of `functional` with its MCSE from the x and y chains.
+pub fn estimate(
+    functional: &Functional,
+    xs: &[Vec<f64>],
+    ys: &[Vec<f64>],
+    method: McseMethod,
+) -> Option<Estimate> {
+    let pick = |c: &Coordinate| match c {
+        Coordinate::X => xs,
+        Coordinate::Y => ys,
+    };
+    match functional {
+        Functional::Mean(c) => {
+            let chains = pick(c);
+            Some(Estimate {
+                value: mean(chains),
+                mcse: mcse(chains, method)?,
+            })
+        }
+        Functional::Variance(c) => {
+            let chains = pick(c);
+            let m = mean(chains);
+            let squares = map(chains, |v| (v - m) * (v - m));
+            Some(Estimate {
+                value: mean(&squares),
+                mcse: mcse(&squares, method)?,
+            })
+        }
+        Functional::Quantile(c, q) => {
+            // MCSE of the indicator I(v <= quantile) mapped back through the
+            // empirical quantile function.
+            let chains = pick(c);
+            let mut sorted: Vec<f64> = chains.iter().flatten().cloned().collect();
+            if sorted.is_empty() {
+                return None;
+            }
+            sorted.sort_by(|a, b| a.total_cmp(b));
+            let value = quantile(&sorted, *q);
+            let indicator = map(chains, |v| (v <= value) as u8 as f64);
+            let se = mcse(&indicator, method)?;
+            Some(Estimate {
+                value,
+                mcse: (quantile(&sorted, q + se) - quantile(&sorted, q - se)) / 2.0,
+            })
+        }
+        Functional::Probability(region) => {
+            let indicator: Vec<Vec<f64>> = xs
+                .iter()
+                .zip(ys.iter())
+                .map(|(x, y)| {
+                    x.iter()
+                        .zip(y.iter())
+                        .map(|(x, y)| region.contains(*x, *y) as u8 as f64)
+                        .collect()
+                })
+                .collect();
+            Some(Estimate {
+                value: mean(&indicator),
+                mcse: mcse(&indicator, method)?,
+            })
+        }
+    }
+}
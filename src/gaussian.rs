@@ -21,3 +21,92 @@ pub fn sample_custom(mean: f64, dev: f64) -> f64 {
     let normal = Normal::new(mean, dev).unwrap();
     normal.sample(&mut rand::thread_rng())
 }
+
+pub fn density_2d(mean: (f64, f64), dev: (f64, f64), x: f64, y: f64) -> f64 {
+    distribution_density(mean.0, dev.0, x) * distribution_density(mean.1, dev.1, y)
+}
+
+pub fn log_density(mean: f64, stddev: f64, state: f64) -> f64 {
+    if stddev == 0.0 {
+        panic!("Divide by zero for stddev");
+    }
+
+    let z = (state - mean) / stddev;
+    -0.5 * z * z - stddev.ln() - 0.5 * (2.0 * PI).ln()
+}
+
+pub fn log_density_2d(mean: (f64, f64), dev: (f64, f64), x: f64, y: f64) -> f64 {
+    log_density(mean.0, dev.0, x) + log_density(mean.1, dev.1, y)
+}
+
+/// Quantile function of the standard normal (Acklam's rational approximation,
+/// relative error below 1.2e-9).
+pub fn inverse_cdf(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e+01,
+        2.209460984245205e+02,
+        -2.759285104469687e+02,
+        1.38357751867269e+02,
+        -3.066479806614716e+01,
+        2.506628277459239e+00,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e+01,
+        1.615858368580409e+02,
+        -1.556989798598866e+02,
+        6.680131188771972e+01,
+        -1.328068155288572e+01,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-03,
+        -3.223964580411365e-01,
+        -2.400758277161838e+00,
+        -2.549732539343734e+00,
+        4.374664141464968e+00,
+        2.938163982698783e+00,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-03,
+        3.224671290700398e-01,
+        2.445134137142996e+00,
+        3.754408661907416e+00,
+    ];
+    const LOW: f64 = 0.02425;
+
+    if p <= 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    if p >= 1.0 {
+        return f64::INFINITY;
+    }
+    if p < LOW {
+        let q = (-2.0 * p.ln()).sqrt();
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    } else if p <= 1.0 - LOW {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    } else {
+        -inverse_cdf(1.0 - p)
+    }
+}
+
+/// Error function (Abramowitz and Stegun 7.1.26, absolute error below 1.5e-7).
+pub fn erf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.3275911 * x.abs());
+    let poly = t
+        * (0.254829592
+            + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let y = 1.0 - poly * (-x * x).exp();
+    if x >= 0.0 {
+        y
+    } else {
+        -y
+    }
+}
+
+pub fn cdf(mean: f64, stddev: f64, state: f64) -> f64 {
+    0.5 * (1.0 + erf((state - mean) / (stddev * std::f64::consts::SQRT_2)))
+}
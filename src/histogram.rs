@@ -0,0 +1,143 @@
+/**
+ * Fixed-range histogram used to accumulate the draws the chains produce.
+ * Values outside [min, max) are counted in `total` but not binned, so the
+ * densities stay normalised against all draws.
+ */
+
+#[derive(Debug, Clone)]
+pub struct Histogram {
+    pub min: f64,
+    pub max: f64,
+    counts: Vec<usize>,
+    total: usize,
+}
+
+impl Histogram {
+    pub fn new(min: f64, max: f64, bins: usize) -> Self {
+        Histogram {
+            min,
+            max,
+            counts: vec![0; bins],
+            total: 0,
+        }
+    }
+
+    pub fn bins(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn bin_width(&self) -> f64 {
+        (self.max - self.min) / self.counts.len() as f64
+    }
+
+    pub fn bin_of(&self, value: f64) -> Option<usize> {
+        if !(self.min..self.max).contains(&value) {
+            return None;
+        }
+        let bin = ((value - self.min) / self.bin_width()) as usize;
+        Some(bin.min(self.counts.len() - 1))
+    }
+
+    pub fn add(&mut self, value: f64) {
+        self.total += 1;
+        if let Some(bin) = self.bin_of(value) {
+            self.counts[bin] += 1;
+        }
+    }
+
+    /// Takes back a value previously passed to `add`.
+    pub fn remove(&mut self, value: f64) {
+        self.total = self.total.saturating_sub(1);
+        if let Some(bin) = self.bin_of(value) {
+            self.counts[bin] = self.counts[bin].saturating_sub(1);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.counts.iter_mut().for_each(|c| *c = 0);
+        self.total = 0;
+    }
+
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    pub fn count(&self, bin: usize) -> usize {
+        self.counts[bin]
+    }
+
+    /// Lower edge of `bin`.
+    pub fn bin_start(&self, bin: usize) -> f64 {
+        self.min + bin as f64 * self.bin_width()
+    }
+
+    /// Estimated probability density in `bin`.
+    pub fn density(&self, bin: usize) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.counts[bin] as f64 / (self.total as f64 * self.bin_width())
+    }
+}
+
+/// 2D counterpart of `Histogram` over a rectangular grid.
+#[derive(Debug, Clone)]
+pub struct Histogram2d {
+    pub x: Histogram,
+    pub y: Histogram,
+    counts: Vec<usize>,
+    total: usize,
+}
+
+impl Histogram2d {
+    pub fn new(x_range: (f64, f64), y_range: (f64, f64), bins: (usize, usize)) -> Self {
+        Histogram2d {
+            x: Histogram::new(x_range.0, x_range.1, bins.0),
+            y: Histogram::new(y_range.0, y_range.1, bins.1),
+            counts: vec![0; bins.0 * bins.1],
+            total: 0,
+        }
+    }
+
+    pub fn add(&mut self, x: f64, y: f64) {
+        self.total += 1;
+        if let (Some(i), Some(j)) = (self.x.bin_of(x), self.y.bin_of(y)) {
+            self.counts[j * self.x.bins() + i] += 1;
+        }
+    }
+
+    /// Takes back a point previously passed to `add`.
+    pub fn remove(&mut self, x: f64, y: f64) {
+        self.total = self.total.saturating_sub(1);
+        if let (Some(i), Some(j)) = (self.x.bin_of(x), self.y.bin_of(y)) {
+            let cell = j * self.x.bins() + i;
+            self.counts[cell] = self.counts[cell].saturating_sub(1);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.counts.iter_mut().for_each(|c| *c = 0);
+        self.total = 0;
+    }
+
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    pub fn count(&self, i: usize, j: usize) -> usize {
+        self.counts[j * self.x.bins() + i]
+    }
+
+    pub fn max_count(&self) -> usize {
+        self.counts.iter().cloned().max().unwrap_or(0)
+    }
+
+    /// Estimated probability density in cell (i, j).
+    pub fn density(&self, i: usize, j: usize) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        let area = self.x.bin_width() * self.y.bin_width();
+        self.count(i, j) as f64 / (self.total as f64 * area)
+    }
+}
@@ -0,0 +1,125 @@
+/**
+ * Bounded per-chain record of the samples a player has visited. Positions are
+ * stored in model coordinates (not stage pixels) so diagnostics can be
+ * computed directly from them.
+ */
+use iced::Point;
+use std::collections::VecDeque;
+
+pub const DEFAULT_CAPACITY: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    pub position: Point,
+    pub log_density: f64,
+    pub accepted: bool,
+    // index of the iteration that produced this sample.
+    pub step: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct History {
+    capacity: usize,
+    // keep every `thinning`-th sample.
+    thinning: usize,
+    steps: usize,
+    samples: VecDeque<Sample>,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        History::new(DEFAULT_CAPACITY, 1)
+    }
+}
+
+impl History {
+    pub fn new(capacity: usize, thinning: usize) -> Self {
+        History {
+            capacity,
+            thinning: thinning.max(1),
+            steps: 0,
+            samples: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Records one iteration and returns whether the sample was kept, i.e.
+    /// not thinned out.
+    pub fn push(&mut self, position: Point, log_density: f64, accepted: bool) -> bool {
+        let step = self.steps;
+        self.steps += 1;
+        if step % self.thinning != 0 || self.capacity == 0 {
+            return false;
+        }
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(Sample {
+            position,
+            log_density,
+            accepted,
+            step,
+        });
+        true
+    }
+
+    /// Drops the oldest `count` stored samples, e.g. to apply a burn-in, and
+    /// returns them.
+    pub fn discard(&mut self, count: usize) -> Vec<Sample> {
+        self.samples
+            .drain(..count.min(self.samples.len()))
+            .collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.steps = 0;
+        self.samples.clear();
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.samples.len() > capacity {
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn thinning(&self) -> usize {
+        self.thinning
+    }
+
+    pub fn set_thinning(&mut self, thinning: usize) {
+        self.thinning = thinning.max(1);
+    }
+
+    /// Number of iterations pushed, including the ones thinned out or evicted.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn last(&self) -> Option<&Sample> {
+        self.samples.back()
+    }
+
+    pub fn samples(&self) -> impl DoubleEndedIterator<Item = &Sample> + ExactSizeIterator {
+        self.samples.iter()
+    }
+
+    pub fn xs(&self) -> Vec<f64> {
+        self.samples.iter().map(|s| s.position.x as f64).collect()
+    }
+
+    pub fn ys(&self) -> Vec<f64> {
+        self.samples.iter().map(|s| s.position.y as f64).collect()
+    }
+}
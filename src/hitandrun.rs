@@ -0,0 +1,120 @@
+/**
+ * Hit-and-run style samplers. Each step picks a uniformly random direction
+ * through the current point and then samples along that line, either with a
+ * 1D slice sampler or with a Metropolis proposal. Both work with log densities
+ * so that points far out in the tails, where the density itself underflows to
+ * zero, still know which way is uphill. Constraints (e.g. polytopes) are
+ * expressed by the log density returning -inf outside the feasible region.
+ */
+use crate::gaussian;
+
+use iced::Point;
+use rand::Rng;
+use std::f64::consts::PI;
+
+const MAX_STEP_OUT: usize = 32;
+const MAX_SHRINK: usize = 100;
+
+pub struct LineStep {
+    pub position: Point,
+    // endpoints of the line segment that was searched, useful for drawing.
+    pub line: (Point, Point),
+    pub accepted: bool,
+}
+
+pub fn random_direction() -> (f64, f64) {
+    let theta = rand::thread_rng().gen_range(0.0..2.0 * PI);
+    (theta.cos(), theta.sin())
+}
+
+fn along(position: Point, direction: (f64, f64), t: f64) -> Point {
+    Point {
+        x: (position.x as f64 + t * direction.0) as f32,
+        y: (position.y as f64 + t * direction.1) as f32,
+    }
+}
+
+/// Hit-and-run with a stepping-out/shrinkage slice sampler along the line.
+pub fn slice_step_2d<F>(log_density: F, position: Point, width: f64) -> LineStep
+where
+    F: Fn(Point) -> f64,
+{
+    let mut rng = rand::thread_rng();
+    let direction = random_direction();
+    let f = |t: f64| log_density(along(position, direction, t));
+
+    let level = f(0.0) + rng.gen::<f64>().ln();
+    let mut left = -rng.gen::<f64>() * width;
+    let mut right = left + width;
+    for _ in 0..MAX_STEP_OUT {
+        if f(left) <= level {
+            break;
+        }
+        left -= width;
+    }
+    for _ in 0..MAX_STEP_OUT {
+        if f(right) <= level {
+            break;
+        }
+        right += width;
+    }
+    let line = (
+        along(position, direction, left),
+        along(position, direction, right),
+    );
+
+    for _ in 0..MAX_SHRINK {
+        let t = rng.gen_range(left..right);
+        if f(t) > level {
+            return LineStep {
+                position: along(position, direction, t),
+                line,
+                accepted: true,
+            };
+        }
+        if t < 0.0 {
+            left = t;
+        } else {
+            right = t;
+        }
+    }
+    LineStep {
+        position,
+        line,
+        accepted: false,
+    }
+}
+
+/// Random-direction Metropolis: a Gaussian step of size `scale` along the line.
+/// A chain outside the support accepts any move.
+pub fn random_direction_step_2d<F>(log_density: F, position: Point, scale: f64) -> LineStep
+where
+    F: Fn(Point) -> f64,
+{
+    let direction = random_direction();
+    let t = gaussian::sample_custom(0.0, scale);
+    let candidate = along(position, direction, t);
+    let line = (
+        along(position, direction, -2.0 * scale),
+        along(position, direction, 2.0 * scale),
+    );
+
+    let current = log_density(position);
+    let log_ratio = log_density(candidate) - current;
+    if current == f64::NEG_INFINITY
+        || log_ratio >= 0.0
+        || rand::thread_rng().gen::<f64>().ln() < log_ratio
+    {
+        LineStep {
+            position: candidate,
+            line,
+            accepted: true,
+        }
+    } else {
+        LineStep {
+            position,
+            line,
+            accepted: false,
+        }
+    }
+}
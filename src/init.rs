@@ -0,0 +1,113 @@
+/**
+ * Strategies for choosing where a population of chains starts.
+ */
+use crate::gaussian;
+
+use iced::Point;
+use rand::Rng;
+use std::fmt;
+
+// how many times wider than the target overdispersed starts are spread.
+pub const OVERDISPERSION: f32 = 3.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    SinglePoint,
+    Overdispersed,
+    UniformInView,
+    Prior,
+    PreviousRun,
+}
+
+impl Strategy {
+    pub const ALL: [Strategy; 5] = [
+        Strategy::SinglePoint,
+        Strategy::Overdispersed,
+        Strategy::UniformInView,
+        Strategy::Prior,
+        Strategy::PreviousRun,
+    ];
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Strategy::SinglePoint => "All at one point",
+                Strategy::Overdispersed => "Overdispersed",
+                Strategy::UniformInView => "Uniform in view",
+                Strategy::Prior => "From the prior",
+                Strategy::PreviousRun => "Previous run",
+            }
+        )
+    }
+}
+
+/// What the strategies draw on, in model coordinates. Means and standard
+/// deviations are per axis.
+#[derive(Debug, Clone, Default)]
+pub struct Setting {
+    pub point: Point,
+    pub target_mean: Point,
+    pub target_stddev: Point,
+    // corners of the region uniform starts are spread over.
+    pub region: (Point, Point),
+    pub prior_mean: Point,
+    pub prior_stddev: Point,
+    // final positions of the previous run.
+    pub previous: Vec<Point>,
+}
+
+/// Starting points for `count` chains. Starting from the previous run falls
+/// back to the prior when there was none.
+pub fn positions(strategy: Strategy, count: usize, setting: &Setting) -> Vec<Point> {
+    match strategy {
+        Strategy::SinglePoint => vec![setting.point; count],
+        Strategy::Overdispersed => overdispersed(setting.target_mean, setting.target_stddev, count),
+        Strategy::UniformInView => uniform(setting.region.0, setting.region.1, count),
+        Strategy::Prior => normal(setting.prior_mean, setting.prior_stddev, count),
+        Strategy::PreviousRun if setting.previous.is_empty() => {
+            normal(setting.prior_mean, setting.prior_stddev, count)
+        }
+        Strategy::PreviousRun => previous(&setting.previous, count),
+    }
+}
+
+/// Independent normal draws with the given mean and deviation per axis.
+pub fn normal(mean: Point, stddev: Point, count: usize) -> Vec<Point> {
+    (0..count)
+        .map(|_| Point {
+            x: gaussian::sample_custom(mean.x as f64, stddev.x as f64) as f32,
+            y: gaussian::sample_custom(mean.y as f64, stddev.y as f64) as f32,
+        })
+        .collect()
+}
+
+/// Draws spread `OVERDISPERSION` times wider than the target, so that
+/// converged chains must have forgotten where they started.
+pub fn overdispersed(mean: Point, stddev: Point, count: usize) -> Vec<Point> {
+    let spread = Point {
+        x: stddev.x * OVERDISPERSION,
+        y: stddev.y * OVERDISPERSION,
+    };
+    normal(mean, spread, count)
+}
+
+/// Uniform draws over the rectangle with corners `a` and `b`.
+pub fn uniform(a: Point, b: Point, count: usize) -> Vec<Point> {
+    let mut rng = rand::thread_rng();
+    (0..count)
+        .map(|_| Point {
+            x: a.x + rng.gen::<f32>() * (b.x - a.x),
+            y: a.y + rng.gen::<f32>() * (b.y - a.y),
+        })
+        .collect()
+}
+
+/// The final positions of a previous run, repeated in turn if more chains
+/// are needed than it had.
+pub fn previous(last: &[Point], count: usize) -> Vec<Point> {
+    last.iter().cycle().take(count).cloned().collect()
+}
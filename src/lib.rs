@@ -1,7 +1,26 @@
+pub mod acceptance;
+pub mod annealing;
 pub mod bellcurve;
+pub mod colormap;
+pub mod contour;
+pub mod diagnostics;
+pub mod expectation;
 pub mod gaussian;
+pub mod histogram;
+pub mod history;
+pub mod hitandrun;
+pub mod init;
+pub mod metrics;
 pub mod metropolis;
+pub mod modelpanel;
+pub mod painted;
+pub mod plot;
+pub mod rjmcmc;
+pub mod sgld;
+pub mod smc;
 pub mod stage;
+pub mod stationarity;
+pub mod viewport;
 
 #[cfg(test)]
 mod tests {
@@ -13,4 +32,348 @@ mod tests {
         let sample = gaussian::distribution_density(4.0, 0.4, 3.4);
         assert_eq!((sample * 100.0).floor() / 100.0, 0.32);
     }
+
+    #[test]
+    fn systematic_resample_follows_weights() {
+        let indices = smc::systematic_resample(&[0.0, 1.0, 0.0], 10);
+        assert!(indices.iter().all(|&i| i == 1));
+        assert_eq!(smc::ess(&[0.25; 4]), 4.0);
+        assert!(smc::systematic_resample(&[], 10).is_empty());
+    }
+
+    #[test]
+    fn line_samplers_climb_from_where_the_density_underflows() {
+        use iced::Point;
+        let log_density =
+            |p: Point| gaussian::log_density_2d((1.0, 1.0), (0.2, 0.2), p.x as f64, p.y as f64);
+        let start = Point::new(10.0, 10.0);
+        // ~60 std. devs. out, where the density itself is 0.
+        assert_eq!(
+            gaussian::density_2d((1.0, 1.0), (0.2, 0.2), 10.0, 10.0),
+            0.0
+        );
+        let target = Point::new(1.0, 1.0);
+        let mut slice = start;
+        for _ in 0..300 {
+            slice = hitandrun::slice_step_2d(log_density, slice, 0.5).position;
+        }
+        let mut walk = start;
+        for _ in 0..3000 {
+            walk = hitandrun::random_direction_step_2d(log_density, walk, 0.2).position;
+        }
+        assert!(slice.distance(target) < 1.0, "slice ended at {:?}", slice);
+        assert!(walk.distance(target) < 1.0, "walk ended at {:?}", walk);
+    }
+
+    #[test]
+    fn annealing_cools_and_beta_one_is_metropolis() {
+        use annealing::{Annealer, Schedule};
+        use iced::Point;
+        for schedule in Schedule::ALL {
+            let mut annealer = Annealer::new(schedule, 10.0);
+            let mut last = annealer.temperature();
+            for _ in 0..1000 {
+                annealer.advance();
+                let temperature = annealer.temperature();
+                assert!(temperature <= last, "{} heated up", schedule);
+                last = temperature;
+            }
+            assert!(last < 10.0);
+        }
+        let (current, candidate) = (Point::new(2.3, 1.6), Point::new(2.1, 1.9));
+        assert_eq!(
+            metropolis::tempered_acceptance_2d((2.0, 2.0), (0.2, 0.2), current, candidate, 1.0),
+            metropolis::acceptance_2d((2.0, 2.0), (0.2, 0.2), current, candidate)
+        );
+    }
+
+    #[test]
+    fn full_batch_gradient_vanishes_at_the_posterior_mean() {
+        use iced::Point;
+        let data = sgld::Dataset::synthetic(50, (1.5, 2.0), 1.0);
+        let posterior = data.posterior();
+        let mean = Point::new(posterior.mean.0 as f32, posterior.mean.1 as f32);
+        let grad = data.minibatch_gradient(mean, data.len());
+        assert!(grad.0.abs() < 1e-3 && grad.1.abs() < 1e-3, "{:?}", grad);
+        let peak = posterior.log_density(mean);
+        for (dx, dy) in [(0.05, 0.0), (-0.05, 0.0), (0.0, 0.05), (0.0, -0.05)] {
+            assert!(posterior.log_density(mean + iced::Vector::new(dx, dy)) < peak);
+        }
+    }
+
+    #[test]
+    fn empty_dataset_gradient_is_the_prior_gradient() {
+        let data = sgld::Dataset {
+            x: Vec::new(),
+            y: Vec::new(),
+            noise: 1.0,
+            prior_dev: 2.0,
+        };
+        let grad = data.minibatch_gradient(iced::Point::new(1.0, -2.0), 10);
+        assert_eq!(grad, (-0.25, 0.5));
+    }
+
+    #[test]
+    fn reversible_jump_without_data_visits_models_as_the_prior() {
+        use rjmcmc::{MixtureData, Model, ReversibleJump};
+        // no data: the posterior is the prior, which gives both models 1/2.
+        let data = MixtureData {
+            y: Vec::new(),
+            noise: 0.5,
+            prior_mean: 0.0,
+            prior_dev: 1.0,
+        };
+        let mut rj = ReversibleJump::new(data, vec![Model::One { mean: 0.0 }; 20]);
+        for _ in 0..4000 {
+            rj.step();
+        }
+        let one = rj.summary.probability(1);
+        assert!((one - 0.5).abs() < 0.05, "P(one component) = {}", one);
+        assert!(rj.jumps_accepted > 0);
+        let empty = MixtureData::synthetic(0, 0.25, (1.0, 3.0), 0.5);
+        assert_eq!(empty.prior_mean, 2.5);
+    }
+
+    #[test]
+    fn history_thins_and_evicts() {
+        let mut chain = history::History::new(3, 2);
+        for i in 0..10 {
+            chain.push(iced::Point::new(i as f32, 0.0), 0.0, true);
+        }
+        assert_eq!(chain.steps(), 10);
+        assert_eq!(chain.xs(), vec![4.0, 6.0, 8.0]);
+    }
+
+    #[test]
+    fn burn_in_discards_aligned_draws_from_chains_and_histograms() {
+        let mut stage = stage::Stage::default();
+        stage.set_players(vec![iced::Point::ORIGIN; 2]);
+        // the second chain stores three fewer draws.
+        for (chain, draws) in [(0, 8), (1, 5)] {
+            for i in 0..draws {
+                let position = iced::Point::new(2.0, i as f32);
+                stage.players[chain].history.push(position, 0.0, true);
+                stage.record_draw(position);
+            }
+        }
+        stage.apply_burn_in(2);
+        let (xs, ys) = stage.chains();
+        assert!(xs.iter().all(|chain| chain.len() == 3));
+        assert_eq!(stage.players[0].history.len(), 3);
+        assert_eq!(ys, vec![vec![5.0, 6.0, 7.0], vec![2.0, 3.0, 4.0]]);
+        assert_eq!(stage.x_histogram.total(), 6);
+        assert_eq!(stage.heatmap.total(), 6);
+    }
+
+    #[test]
+    fn acceptance_rate_tracks_overall_and_window() {
+        let mut rate = acceptance::AcceptanceRate::new(3);
+        assert_eq!(rate.overall(), None);
+        for accepted in [true, false, true, true] {
+            rate.record(accepted);
+        }
+        assert_eq!(rate.overall(), Some(0.75));
+        // the first accept has left the window.
+        assert_eq!(rate.recent(), Some(2.0 / 3.0));
+        rate.record(false);
+        rate.record(false);
+        assert_eq!(rate.recent(), Some(1.0 / 3.0));
+        rate.clear();
+        assert_eq!(rate.recent(), None);
+    }
+
+    #[test]
+    fn ess_of_independent_draws_is_close_to_draw_count() {
+        let chains: Vec<Vec<f64>> = (0..4)
+            .map(|_| {
+                (0..500)
+                    .map(|_| gaussian::sample_custom(0.0, 1.0))
+                    .collect()
+            })
+            .collect();
+        let ess = diagnostics::bulk_ess(&chains).unwrap();
+        assert!(ess > 1000.0 && ess < 4000.0, "ess {}", ess);
+        assert!((gaussian::inverse_cdf(0.975) - 1.959964).abs() < 1e-5);
+    }
+
+    #[test]
+    fn rhat_flags_chains_in_different_places() {
+        let chains = |offset: f64| -> Vec<Vec<f64>> {
+            (0..4)
+                .map(|c| {
+                    (0..200)
+                        .map(|_| gaussian::sample_custom(offset * c as f64, 1.0))
+                        .collect()
+                })
+                .collect()
+        };
+        assert!(diagnostics::rank_rhat(&chains(0.0)).unwrap() < 1.05);
+        assert!(diagnostics::split_rhat(&chains(5.0)).unwrap() > 1.5);
+    }
+
+    #[test]
+    fn distances_shrink_for_target_draws() {
+        let target = metrics::GaussianTarget::independent((1.0, 1.0), (0.2, 0.2));
+        let draws = |mean: f64| -> Vec<iced::Point> {
+            (0..2000)
+                .map(|_| {
+                    iced::Point::new(
+                        gaussian::sample_custom(mean, 0.2) as f32,
+                        gaussian::sample_custom(1.0, 0.2) as f32,
+                    )
+                })
+                .collect()
+        };
+        let good = metrics::sliced_wasserstein(&draws(1.0), &target, 8).unwrap();
+        let bad = metrics::sliced_wasserstein(&draws(2.0), &target, 8).unwrap();
+        assert!(good < 0.05 && bad > 0.3, "good {} bad {}", good, bad);
+        assert!(metrics::distances(&[], &target).is_none());
+        assert!((gaussian::cdf(0.0, 1.0, 1.96) - 0.975).abs() < 1e-4);
+    }
+
+    #[test]
+    fn heidelberger_welch_discards_transient() {
+        let chain: Vec<f64> = (0..1000)
+            .map(|i| 5.0 * (-(i as f64) / 30.0).exp() + gaussian::sample_custom(1.0, 0.1))
+            .collect();
+        let hw = stationarity::heidelberger_welch(&chain, 0.1).unwrap();
+        assert!(hw.stationary && hw.start > 0);
+        assert!(stationarity::geweke(&chain, 0.1, 0.5).unwrap() > 1.96);
+    }
+
+    #[test]
+    fn mcse_methods_agree_on_independent_draws() {
+        use expectation::{Coordinate, Functional, McseMethod};
+        let chains: Vec<Vec<f64>> = (0..8)
+            .map(|_| {
+                (0..400)
+                    .map(|_| gaussian::sample_custom(2.0, 1.0))
+                    .collect()
+            })
+            .collect();
+        let mean = Functional::Mean(Coordinate::X);
+        let ess = expectation::estimate(&mean, &chains, &chains, McseMethod::Ess).unwrap();
+        let batch = expectation::estimate(&mean, &chains, &chains, McseMethod::BatchMeans).unwrap();
+        // sd / sqrt(3200) ~ 0.018
+        assert!((ess.value - 2.0).abs() < 0.1);
+        assert!(ess.mcse > 0.01 && ess.mcse < 0.03);
+        assert!(batch.mcse > 0.01 && batch.mcse < 0.03);
+    }
+
+    #[test]
+    fn viewport_round_trips_and_flips_y() {
+        use iced::{Point, Size};
+        let view = viewport::Viewport::new(Point::new(0.0, 0.0), Point::new(4.0, 2.0));
+        let size = Size::new(800.0, 600.0);
+        // width limits the scale, so the y range shows more than requested.
+        assert_eq!(view.scale(size), 200.0);
+        let origin = view.to_screen(Point::new(0.0, 0.0), size);
+        assert_eq!(origin, Point::new(0.0, 500.0));
+        let p = Point::new(1.25, 3.5);
+        let back = view.to_model(view.to_screen(p, size), size);
+        assert!(back.distance(p) < 1e-5);
+    }
+
+    #[test]
+    fn ticks_land_on_round_values() {
+        let (ticks, step) = viewport::ticks(0.03, 0.97, 5);
+        assert_eq!(step, 0.2);
+        assert_eq!(ticks.len(), 4);
+        assert_eq!(viewport::tick_label(ticks[1], step), "0.4");
+        let (ticks, step) = viewport::ticks(-12.0, 37.0, 5);
+        assert_eq!(step, 10.0);
+        assert_eq!(ticks, vec![-10.0, 0.0, 10.0, 20.0, 30.0]);
+        assert_eq!(viewport::tick_label(0.0, step), "0");
+    }
+
+    #[test]
+    fn hpd_contours_of_a_gaussian_are_circles() {
+        use iced::Point;
+        let grid = contour::Grid::sample(
+            |p| gaussian::density_2d((0.0, 0.0), (1.0, 1.0), p.x as f64, p.y as f64),
+            Point::new(-5.0, -5.0),
+            Point::new(5.0, 5.0),
+            201,
+            201,
+        );
+        // for a standard 2D normal the p-HPD threshold is (1 - p) * peak.
+        let peak = grid.max();
+        let levels = contour::hpd_levels(&grid, &[0.5, 0.9]);
+        assert!((levels[0] / peak - 0.5).abs() < 0.02);
+        assert!((levels[1] / peak - 0.1).abs() < 0.02);
+        // and it is the circle of radius sqrt(2 ln 2).
+        let segments = contour::marching_squares(&grid, 0.5 * peak);
+        assert!(!segments.is_empty());
+        for (a, _) in segments {
+            let radius = ((a.x - 100.0).powi(2) + (a.y - 100.0).powi(2)).sqrt() / 20.0;
+            assert!((radius - 1.1774).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn explained_step_matches_its_decision() {
+        use iced::Point;
+        for _ in 0..100 {
+            let step = metropolis::explained_step_2d((2.0, 2.0), (0.2, 0.2), Point::new(2.3, 1.6));
+            let expected =
+                metropolis::acceptance_2d((2.0, 2.0), (0.2, 0.2), step.current, step.candidate);
+            assert_eq!(step.acceptance, expected);
+            let (x, y) = step.accepted();
+            assert_eq!(
+                step.next.x,
+                if x { step.candidate.x } else { step.current.x }
+            );
+            assert_eq!(
+                step.next.y,
+                if y { step.candidate.y } else { step.current.y }
+            );
+        }
+    }
+
+    #[test]
+    fn initialisation_strategies_place_chains() {
+        use iced::Point;
+        let setting = init::Setting {
+            point: Point::new(-1.0, 5.0),
+            target_mean: Point::new(2.0, 2.0),
+            target_stddev: Point::new(0.2, 0.2),
+            region: (Point::new(0.0, 1.0), Point::new(4.0, 2.0)),
+            previous: vec![Point::new(1.0, 1.0), Point::new(3.0, 3.0)],
+            ..Default::default()
+        };
+        let single = init::positions(init::Strategy::SinglePoint, 10, &setting);
+        assert!(single.iter().all(|p| *p == setting.point));
+        let uniform = init::positions(init::Strategy::UniformInView, 500, &setting);
+        assert!(uniform
+            .iter()
+            .all(|p| (0.0..=4.0).contains(&p.x) && (1.0..=2.0).contains(&p.y)));
+        // overdispersed starts are spread wider than the target itself.
+        let spread = init::positions(init::Strategy::Overdispersed, 2000, &setting);
+        let var = spread.iter().map(|p| (p.x - 2.0).powi(2)).sum::<f32>() / 2000.0;
+        assert!(var.sqrt() > 0.4);
+        let previous = init::positions(init::Strategy::PreviousRun, 3, &setting);
+        assert_eq!(previous[2], setting.previous[0]);
+    }
+
+    #[test]
+    fn painted_density_interpolates_and_normalises_marginals() {
+        use iced::Point;
+        let mut grid =
+            painted::GridDensity::new(Point::new(0.0, 0.0), Point::new(4.0, 4.0), 81, 81);
+        assert!(grid.is_empty());
+        grid.brush(Point::new(2.0, 2.0), 1.0, 1.0);
+        let centre = grid.density(Point::new(2.0, 2.0));
+        assert!((centre - 1.0).abs() < 1e-6);
+        // halfway between nodes the value is interpolated, not stepped.
+        let between = grid.density(Point::new(2.025, 2.0));
+        assert!(between < centre && between > grid.density(Point::new(2.05, 2.0)));
+        assert_eq!(grid.density(Point::new(9.0, 2.0)), painted::BACKGROUND);
+        // marginals integrate to one.
+        let mass: f64 = (0..400)
+            .map(|i| grid.marginal(i as f32 * 0.01, true) * 0.01)
+            .sum();
+        assert!((mass - 1.0).abs() < 0.02);
+        grid.brush(Point::new(2.0, 2.0), 1.0, -5.0);
+        assert!(grid.is_empty());
+    }
 }
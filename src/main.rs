@@ -1,19 +1,66 @@
-use iced::widget::{button, column, container, row, slider, text, Canvas};
+use iced::widget::{
+    button, column, container, pick_list, row, scrollable, slider, text, tooltip, Canvas,
+};
 use iced::{application, executor, theme, time, Renderer};
 use iced::{Application, Color, Command, Element, Length, Point, Settings, Subscription, Theme};
 
+use std::fmt;
 use std::time::{Duration, Instant};
 
+use mcmc::acceptance;
+use mcmc::annealing::{Annealer, Schedule};
 use mcmc::bellcurve::BellCurve;
+use mcmc::diagnostics::{self, ChainSummary};
+use mcmc::expectation::{self, Coordinate, Estimate, Functional, McseMethod, Region};
 use mcmc::gaussian;
-use mcmc::metropolis;
-use mcmc::stage::{Player, Stage};
+use mcmc::hitandrun;
+use mcmc::init::{self, Strategy};
+use mcmc::metrics::{self, Distances, GaussianTarget};
+use mcmc::metropolis::{self, Candidate};
+use mcmc::modelpanel::ModelPanel;
+use mcmc::plot::{LinePlot, Series};
+use mcmc::rjmcmc::{MixtureData, Model, ReversibleJump};
+use mcmc::sgld::{Dataset, Sgld};
+use mcmc::smc::{Resampling, Smc};
+use mcmc::stage::{HeatmapMode, Stage, StageEvent, Target, Tool, SELECTION_COLORS};
+use mcmc::stationarity::{self, ChainReport};
+use mcmc::viewport::Viewport;
 
 const X_MEAN: f64 = 1.0;
 const Y_MEAN: f64 = 1.0;
 const X_STDDEV: f64 = 0.2;
 const Y_STDDEV: f64 = 0.2;
 const SPEED: u128 = 10;
+const LINE_WIDTH: f64 = 0.5;
+const LINE_SCALE: f64 = 0.2;
+const PRIOR_MEAN: f64 = 2.0;
+const PRIOR_STDDEV: f64 = 0.8;
+const PRIOR: Point = Point {
+    x: PRIOR_MEAN as f32,
+    y: PRIOR_MEAN as f32,
+};
+const PRIOR_SPREAD: Point = Point {
+    x: PRIOR_STDDEV as f32,
+    y: PRIOR_STDDEV as f32,
+};
+const SMC_DELTA: f64 = 0.01;
+const INITIAL_TEMPERATURE: f64 = 10.0;
+// model distance the bell curve's dot moves per frame.
+const CURVE_STEP: f32 = 0.004;
+const DEFAULT_BATCH_SIZE: u32 = 20;
+const DATASET_SIZE: usize = 50;
+const DATASET_WEIGHTS: (f64, f64) = (1.5, 2.0);
+const DATASET_NOISE: f64 = 1.0;
+const MIXTURE_SIZE: usize = 200;
+const MIXTURE_WEIGHT: f64 = 0.4;
+const MIXTURE_MEANS: (f64, f64) = (1.0, 3.0);
+const MIXTURE_NOISE: f64 = 0.5;
+const ACF_LAGS: usize = 40;
+// recompute the chain diagnostics every this many sampling ticks.
+const DIAGNOSTICS_EVERY: usize = 20;
+const RHAT_THRESHOLD: f64 = 1.01;
+// number of most recent draws shown in the trace plots.
+const TRACE_LENGTH: usize = 300;
 
 pub fn main() -> iced::Result {
     MetropolisVisualizer::run(Settings {
@@ -22,11 +69,127 @@ pub fn main() -> iced::Result {
     })
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sampler {
+    Metropolis,
+    HitAndRun,
+    RandomDirection,
+    Smc,
+    Annealing,
+    Sgld,
+    Sghmc,
+    ReversibleJump,
+}
+
+impl Sampler {
+    const ALL: [Sampler; 8] = [
+        Sampler::Metropolis,
+        Sampler::HitAndRun,
+        Sampler::RandomDirection,
+        Sampler::Smc,
+        Sampler::Annealing,
+        Sampler::Sgld,
+        Sampler::Sghmc,
+        Sampler::ReversibleJump,
+    ];
+}
+
+impl fmt::Display for Sampler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Sampler::Metropolis => "Metropolis",
+                Sampler::HitAndRun => "Hit-and-run (slice)",
+                Sampler::RandomDirection => "Random direction",
+                Sampler::Smc => "Sequential Monte Carlo",
+                Sampler::Annealing => "Simulated annealing",
+                Sampler::Sgld => "SGLD",
+                Sampler::Sghmc => "SGHMC",
+                Sampler::ReversibleJump => "Reversible jump",
+            }
+        )
+    }
+}
+
+/// Kind of expectation functional being added in the expectations panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FunctionalKind {
+    Mean,
+    Variance,
+    Quantile,
+    Probability,
+}
+
+impl FunctionalKind {
+    const ALL: [FunctionalKind; 4] = [
+        FunctionalKind::Mean,
+        FunctionalKind::Variance,
+        FunctionalKind::Quantile,
+        FunctionalKind::Probability,
+    ];
+}
+
+impl fmt::Display for FunctionalKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                FunctionalKind::Mean => "Mean",
+                FunctionalKind::Variance => "Variance",
+                FunctionalKind::Quantile => "Quantile",
+                FunctionalKind::Probability => "P(coordinate >= t)",
+            }
+        )
+    }
+}
+
 struct MetropolisVisualizer {
     is_playing: bool,
+    // players started by dragging a region with the place tool.
+    batch_size: u32,
+    // where Reset starts the players, and where the last run ended.
+    init_strategy: Strategy,
+    previous_run: Vec<Point>,
+    sampler: Sampler,
+    smc: Smc,
+    annealer: Annealer,
+    dataset: Dataset,
+    sgld: Sgld,
+    sgld_step_slider: u32,
+    sgld_decay_slider: u32,
+    sgld_batch_slider: u32,
+    rj: ReversibleJump,
+    model_panel: ModelPanel,
     speed: i32,
     stage: Stage,
     now: Instant,
+    ticks: usize,
+    sampling_time: Duration,
+    chain_summary: ChainSummary,
+    computing_diagnostics: bool,
+    // bumped whenever the diagnostics are reset or the expectations change, so
+    // results computed for an earlier run or set of functionals can be told
+    // apart and dropped.
+    diagnostics_generation: usize,
+    acf_plot: LinePlot,
+    // distances to the target, keyed by the tick they were computed at.
+    distance_log: Vec<(usize, Distances)>,
+    distance_plot: LinePlot,
+    stationarity: Vec<ChainReport>,
+    expectations: Vec<Functional>,
+    // running estimates of every functional, keyed by tick.
+    expectation_log: Vec<Vec<(usize, Estimate)>>,
+    mcse_method: McseMethod,
+    functional_kind: FunctionalKind,
+    functional_coordinate: Coordinate,
+    functional_slider: u32,
+    plotted_expectation: Option<usize>,
+    expectation_plot: LinePlot,
+    trace_x: LinePlot,
+    trace_y: LinePlot,
     x_curve: BellCurve,
     y_curve: BellCurve,
     x_mean_slider: u32,
@@ -39,17 +202,763 @@ struct MetropolisVisualizer {
     ystddev: f32,
 }
 
-#[derive(Debug, Clone, Copy)]
+/// Results of the diagnostics computed off the UI thread.
+#[derive(Debug, Clone)]
+pub struct Diagnostics {
+    generation: usize,
+    summary: ChainSummary,
+    distances: Option<Distances>,
+    stationarity: Vec<ChainReport>,
+    expectations: Vec<Option<Estimate>>,
+}
+
+#[derive(Debug, Clone)]
 pub enum Message {
     Run(Instant),
     Toggle,
+    Step,
     Reset,
+    SamplerSelected(Sampler),
+    ResamplingSelected(Resampling),
+    ScheduleSelected(Schedule),
+    SgldStepChanged(u32),
+    SgldDecayChanged(u32),
+    SgldBatchChanged(u32),
+    DiagnosticsComputed(Box<Diagnostics>),
+    ApplyBurnIn,
+    McseMethodSelected(McseMethod),
+    FunctionalKindSelected(FunctionalKind),
+    CoordinateSelected(Coordinate),
+    FunctionalSliderChanged(u32),
+    AddExpectation,
+    RemoveExpectation(usize),
+    PlotExpectation(usize),
+    Stage(StageEvent),
+    ResetView,
+    HeatmapSelected(HeatmapMode),
+    ToolSelected(Tool),
+    BatchSizeChanged(u32),
+    ClearPlayers,
+    StrategySelected(Strategy),
+    TargetSelected(Target),
+    BrushRadiusChanged(u32),
+    ToggleErase,
+    SmoothPainting,
+    ClearPainting,
+    TrailLengthChanged(u32),
     XMeanSliderChanged(u32),
     YMeanSliderChanged(u32),
     XStdDevSliderChanged(u32),
     YStdDevSliderChanged(u32),
 }
 
+impl MetropolisVisualizer {
+    fn reset_smc(&mut self) {
+        let resampling = self.smc.resampling;
+        self.smc = Smc::new(self.stage.players.len(), SMC_DELTA);
+        self.smc.resampling = resampling;
+        let prior = init::normal(PRIOR, PRIOR_SPREAD, self.stage.players.len());
+        for (player, position) in self.stage.players.iter_mut().zip(prior) {
+            player.current = position;
+            player.weight = 1.0;
+        }
+    }
+
+    fn acceptance_panel(&self) -> Element<'_, Message, Renderer<Theme>> {
+        let percent = |rate: Option<f64>| match rate {
+            Some(rate) => format!("{:.1}%", rate * 100.0),
+            None => String::from("-"),
+        };
+        let (overall, recent) = self.stage.acceptance_rate();
+        let stuck = self
+            .stage
+            .players
+            .iter()
+            .filter(|player| matches!(player.acceptance.recent(), Some(rate) if rate < 0.05))
+            .count();
+        text(format!(
+            "acceptance overall {}   last {} steps {}   stuck chains (< 5%) {}",
+            percent(overall),
+            acceptance::DEFAULT_WINDOW,
+            percent(recent),
+            stuck
+        ))
+        .into()
+    }
+
+    /// Passes the target set by the sliders on to the stage.
+    fn update_target(&mut self) {
+        self.stage.set_target(
+            Point::new(self.xmean, self.ymean),
+            Point::new(self.xstddev, self.ystddev),
+        );
+    }
+
+    /// What the initialisation strategies start new players from.
+    fn init_setting(&self) -> init::Setting {
+        init_setting(
+            self.stage.viewport,
+            Point::new(self.xmean, self.ymean),
+            Point::new(self.xstddev, self.ystddev),
+            self.previous_run.clone(),
+        )
+    }
+
+    /// Switches the density the chains sample and starts their statistics over.
+    fn select_target(&mut self, target: Target) {
+        self.stage.set_target_kind(target);
+        self.stage.clear_history();
+        if self.sampler == Sampler::Smc {
+            self.reset_smc();
+        }
+        self.reset_annealer();
+        self.reset_diagnostics();
+    }
+
+    fn paint_panel(&self) -> Element<'_, Message, Renderer<Theme>> {
+        let mut panel = row![pick_list(
+            &Target::ALL[..],
+            Some(self.stage.target),
+            Message::TargetSelected
+        )];
+        if self.stage.target == Target::Painted {
+            panel = panel
+                .push(text(format!(" brush {:.2} ", self.stage.brush_radius)))
+                .push(
+                    container(slider(
+                        1..=40,
+                        (self.stage.brush_radius * 20.0).round() as u32,
+                        Message::BrushRadiusChanged,
+                    ))
+                    .width(150),
+                )
+                .push(
+                    button(if self.stage.erasing {
+                        "Erasing"
+                    } else {
+                        "Adding"
+                    })
+                    .on_press(Message::ToggleErase),
+                )
+                .push(button("Smooth").on_press(Message::SmoothPainting))
+                .push(button("Clear painting").on_press(Message::ClearPainting));
+            if self.stage.painted.is_empty() {
+                panel = panel.push(text(
+                    " pick the paint tool and brush density onto the stage",
+                ));
+            }
+        }
+        panel.into()
+    }
+
+    /// Restarts sampling after players were placed or removed by hand,
+    /// keeping their positions.
+    fn players_changed(&mut self) {
+        let resampling = self.smc.resampling;
+        self.smc = Smc::new(self.stage.players.len(), SMC_DELTA);
+        self.smc.resampling = resampling;
+        let count = self.stage.players.len();
+        self.stage.selected.retain(|&index| index < count);
+        self.stage.clear_history();
+        self.reset_annealer();
+        self.reset_sgld();
+        self.reset_rj();
+        self.reset_diagnostics();
+        self.update_traces();
+    }
+
+    fn reset_annealer(&mut self) {
+        self.annealer = Annealer::new(self.annealer.schedule, INITIAL_TEMPERATURE);
+        self.stage.best = None;
+    }
+
+    /// Log density of the target the current sampler is exploring, at model
+    /// coordinates `position` of chain `chain`.
+    fn log_target(&self, position: Point, chain: usize) -> f64 {
+        match self.sampler {
+            Sampler::Sgld | Sampler::Sghmc => self.dataset.posterior().log_density(position),
+            Sampler::ReversibleJump => match self.rj.chains.get(chain) {
+                Some(model) => self.rj.data.log_posterior(model),
+                None => f64::NAN,
+            },
+            _ if self.stage.target == Target::Painted => self.stage.painted.log_density(position),
+            _ => gaussian::log_density_2d(
+                (self.xmean as f64, self.ymean as f64),
+                (self.xstddev as f64, self.ystddev as f64),
+                position.x as f64,
+                position.y as f64,
+            ),
+        }
+    }
+
+    /// Evaluates the sampled density at every player's current position, which
+    /// changes with the players and with the target alike.
+    fn update_log_densities(&mut self) {
+        let log_densities: Vec<f64> = self
+            .stage
+            .players
+            .iter()
+            .enumerate()
+            .map(|(chain, player)| self.log_target(player.current, chain))
+            .collect();
+        for (player, log_density) in self.stage.players.iter_mut().zip(log_densities) {
+            player.log_density = Some(log_density).filter(|l| !l.is_nan());
+        }
+    }
+
+    /// Records the step every chain just took. `accepted` holds each chain's
+    /// Metropolis decision, `None` for samplers that never reject.
+    fn record_history(&mut self, before: &[Point], accepted: &[Option<bool>]) {
+        let positions: Vec<Point> = self.stage.players.iter().map(|p| p.current).collect();
+        let log_densities: Vec<f64> = positions
+            .iter()
+            .enumerate()
+            .map(|(chain, position)| self.log_target(*position, chain))
+            .collect();
+        // only these samplers propose candidates they may reject.
+        let proposes = matches!(self.sampler, Sampler::Metropolis | Sampler::Annealing);
+        let trail_length = self.stage.trail_length;
+        let mut kept = vec![false; positions.len()];
+        for (chain, player) in self.stage.players.iter_mut().enumerate() {
+            let proposal = proposes.then_some(player.candidate.position);
+            player.record_step(before[chain], proposal, trail_length);
+            kept[chain] = player.history.push(
+                positions[chain],
+                log_densities[chain],
+                accepted[chain].unwrap_or(true),
+            );
+            if let Some(accepted) = accepted[chain] {
+                player.acceptance.record(accepted);
+            }
+        }
+        // the histograms count the same thinned draws the histories keep, so a
+        // burn-in can take them back out.
+        for (position, kept) in positions.into_iter().zip(kept) {
+            if kept {
+                self.stage.record_draw(position);
+            }
+        }
+    }
+
+    fn reset_rj(&mut self) {
+        self.rj.chains.clear();
+        self.rj.summary = Default::default();
+        self.rj.iteration = 0;
+        self.rj.jumps_proposed = 0;
+        self.rj.jumps_accepted = 0;
+        if self.sampler == Sampler::ReversibleJump {
+            let prior_mean = self.rj.data.prior_mean;
+            self.rj.chains = self
+                .stage
+                .players
+                .iter()
+                .map(|_| Model::One {
+                    mean: gaussian::sample_custom(prior_mean, 0.5),
+                })
+                .collect();
+        }
+        self.model_panel.summary = self.rj.summary;
+        self.model_panel.redraw();
+    }
+
+    fn stage_view(&self) -> Element<'_, Message, Renderer<Theme>> {
+        let stage: Element<StageEvent, Renderer<Theme>> = Canvas::new(&self.stage)
+            .height(Length::Fill)
+            .width(Length::Fill)
+            .into();
+        let mut side = column![
+            Canvas::new(&self.acf_plot).height(250).width(Length::Fill),
+            self.ess_panel(),
+            self.stationarity_panel(),
+        ];
+        if self.metrics_target().is_some() {
+            side = side.push(
+                Canvas::new(&self.distance_plot)
+                    .height(200)
+                    .width(Length::Fill),
+            );
+        }
+        if !self.stage.selected.is_empty() {
+            side = side
+                .push(Canvas::new(&self.trace_x).height(150).width(Length::Fill))
+                .push(Canvas::new(&self.trace_y).height(150).width(Length::Fill));
+        } else {
+            side = side.push(text("click players on the stage to trace them"));
+        }
+        if self.sampler == Sampler::ReversibleJump {
+            side = side
+                .push(text(format!(
+                    "step {}   jumps accepted {} / {}",
+                    self.rj.iteration, self.rj.jumps_accepted, self.rj.jumps_proposed
+                )))
+                .push(
+                    Canvas::new(&self.model_panel)
+                        .height(300)
+                        .width(Length::Fill),
+                );
+        }
+        side = side.push(self.expectation_panel());
+        row![stage.map(Message::Stage), scrollable(side).width(400)].into()
+    }
+
+    fn ess_panel(&self) -> Element<'_, Message, Renderer<Theme>> {
+        let seconds = self.sampling_time.as_secs_f64();
+        let format_ess = |ess: Option<f64>| match ess {
+            Some(ess) if seconds > 0.0 => format!("{:.0} ({:.0}/s)", ess, ess / seconds),
+            Some(ess) => format!("{:.0}", ess),
+            None => String::from("-"),
+        };
+        let format_rhat = |rhat: Option<f64>| match rhat {
+            Some(rhat) => format!("{:.3}", rhat),
+            None => String::from("-"),
+        };
+        let summary = &self.chain_summary;
+        // R-hat is None until every chain has enough draws.
+        let measured = matches!(
+            (summary.split_rhat, summary.rank_rhat),
+            ((Some(_), Some(_)), (Some(_), Some(_)))
+        );
+        column![
+            text(format!(
+                "bulk ESS  x {}   y {}",
+                format_ess(summary.bulk_ess.0),
+                format_ess(summary.bulk_ess.1)
+            )),
+            text(format!(
+                "tail ESS  x {}   y {}",
+                format_ess(summary.tail_ess.0),
+                format_ess(summary.tail_ess.1)
+            )),
+            text(format!("sampling time {:.2}s", seconds)),
+            text(format!(
+                "split R-hat  x {}   y {}",
+                format_rhat(summary.split_rhat.0),
+                format_rhat(summary.split_rhat.1)
+            )),
+            text(format!(
+                "rank R-hat  x {}   y {}",
+                format_rhat(summary.rank_rhat.0),
+                format_rhat(summary.rank_rhat.1)
+            )),
+            if !measured {
+                text("not enough draws for R-hat").style(Color::from_rgb8(0x88, 0x88, 0x88))
+            } else if summary.converged(RHAT_THRESHOLD) {
+                text(format!("converged (R-hat < {})", RHAT_THRESHOLD))
+                    .style(Color::from_rgb8(0x2a, 0x9d, 0x8f))
+            } else {
+                text(format!("not converged (R-hat >= {})", RHAT_THRESHOLD))
+                    .style(Color::from_rgb8(0xe6, 0x39, 0x46))
+            },
+        ]
+        .into()
+    }
+
+    fn update_diagnostics(&mut self, summary: ChainSummary) {
+        self.chain_summary = summary;
+        let lags = |acf: &Vec<f64>| {
+            acf.iter()
+                .enumerate()
+                .map(|(lag, rho)| (lag as f64, *rho))
+                .collect()
+        };
+        self.acf_plot.set_series(vec![
+            Series {
+                label: String::from("x"),
+                color: Color::from_rgb8(0x12, 0x93, 0xD8),
+                points: lags(&self.chain_summary.acf.0),
+            },
+            Series {
+                label: String::from("y"),
+                color: Color::from_rgb8(0xf4, 0xa2, 0x61),
+                points: lags(&self.chain_summary.acf.1),
+            },
+        ]);
+    }
+
+    fn update_traces(&mut self) {
+        let traces = |coordinate: fn(&Point) -> f32| -> Vec<Series> {
+            self.stage
+                .selected
+                .iter()
+                .enumerate()
+                .filter_map(|(i, index)| {
+                    let history = &self.stage.players.get(*index)?.history;
+                    Some(Series {
+                        label: format!("#{}", index),
+                        color: SELECTION_COLORS[i],
+                        points: history
+                            .samples()
+                            .rev()
+                            .take(TRACE_LENGTH)
+                            .rev()
+                            .map(|sample| (sample.step as f64, coordinate(&sample.position) as f64))
+                            .collect(),
+                    })
+                })
+                .collect()
+        };
+        let xs = traces(|p| p.x);
+        let ys = traces(|p| p.y);
+        self.trace_x.set_series(xs);
+        self.trace_y.set_series(ys);
+    }
+
+    fn reset_diagnostics(&mut self) {
+        self.diagnostics_generation += 1;
+        self.ticks = 0;
+        self.sampling_time = Duration::ZERO;
+        self.chain_summary = ChainSummary::default();
+        self.acf_plot.set_series(Vec::new());
+        self.distance_log.clear();
+        self.distance_plot.set_series(Vec::new());
+        self.stationarity.clear();
+        self.expectation_log.iter_mut().for_each(|log| log.clear());
+        self.update_expectation_plot();
+    }
+
+    /// Functional described by the controls of the expectations panel.
+    fn new_functional(&self) -> Functional {
+        let coordinate = self.functional_coordinate;
+        match self.functional_kind {
+            FunctionalKind::Mean => Functional::Mean(coordinate),
+            FunctionalKind::Variance => Functional::Variance(coordinate),
+            FunctionalKind::Quantile => Functional::Quantile(
+                coordinate,
+                self.functional_slider.clamp(1, 99) as f64 / 100.0,
+            ),
+            FunctionalKind::Probability => {
+                let threshold = self.functional_slider as f64 / 25.0;
+                let mut region = Region {
+                    min: (f64::NEG_INFINITY, f64::NEG_INFINITY),
+                    max: (f64::INFINITY, f64::INFINITY),
+                };
+                match coordinate {
+                    Coordinate::X => region.min.0 = threshold,
+                    Coordinate::Y => region.min.1 = threshold,
+                }
+                Functional::Probability(region)
+            }
+        }
+    }
+
+    fn update_expectation_plot(&mut self) {
+        let log = match self.plotted_expectation {
+            Some(index) => &self.expectation_log[index],
+            None => {
+                self.expectation_plot.set_series(Vec::new());
+                return;
+            }
+        };
+        let series = |label: &str, color: Color, k: f64| Series {
+            label: String::from(label),
+            color,
+            points: log
+                .iter()
+                .map(|(tick, estimate)| (*tick as f64, estimate.value + k * estimate.mcse))
+                .collect(),
+        };
+        let band = Color::from_rgb8(0x55, 0x55, 0x55);
+        let plot = vec![
+            series("estimate", Color::from_rgb8(0x12, 0x93, 0xD8), 0.0),
+            series("+2 MCSE", band, 2.0),
+            series("-2 MCSE", band, -2.0),
+        ];
+        self.expectation_plot.set_series(plot);
+    }
+
+    fn expectation_panel(&self) -> Element<'_, Message, Renderer<Theme>> {
+        let mut panel = column![
+            row![
+                pick_list(
+                    &FunctionalKind::ALL[..],
+                    Some(self.functional_kind),
+                    Message::FunctionalKindSelected
+                ),
+                pick_list(
+                    &Coordinate::ALL[..],
+                    Some(self.functional_coordinate),
+                    Message::CoordinateSelected
+                ),
+                button("Add").on_press(Message::AddExpectation),
+            ],
+            row![
+                text(format!("{} ", self.new_functional())),
+                slider(
+                    0..=100,
+                    self.functional_slider,
+                    Message::FunctionalSliderChanged
+                ),
+            ],
+            row![
+                text("MCSE "),
+                pick_list(
+                    &McseMethod::ALL[..],
+                    Some(self.mcse_method),
+                    Message::McseMethodSelected
+                ),
+            ],
+        ];
+        for (index, (functional, log)) in self
+            .expectations
+            .iter()
+            .zip(self.expectation_log.iter())
+            .enumerate()
+        {
+            let estimate = match log.last() {
+                Some((_, estimate)) => format!("{:.4} +/- {:.4}", estimate.value, estimate.mcse),
+                None => String::from("-"),
+            };
+            panel = panel.push(row![
+                text(format!("{} = {} ", functional, estimate)).width(Length::Fill),
+                button("plot").on_press(Message::PlotExpectation(index)),
+                button("x").on_press(Message::RemoveExpectation(index)),
+            ]);
+        }
+        if self.plotted_expectation.is_some() {
+            panel = panel.push(
+                Canvas::new(&self.expectation_plot)
+                    .height(200)
+                    .width(Length::Fill),
+            );
+        }
+        panel.into()
+    }
+
+    fn stationarity_panel(&self) -> Element<'_, Message, Renderer<Theme>> {
+        if self.stationarity.is_empty() {
+            return column![].into();
+        }
+        let reports = &self.stationarity;
+        let geweke_failed = reports.iter().filter(|r| !r.geweke_passed()).count();
+        let not_stationary = reports.iter().filter(|r| !r.stationary()).count();
+        let burn_in = stationarity::suggest_burn_in(reports);
+        let format_z = |z: Option<f64>| match z {
+            Some(z) => format!("{:.2}", z),
+            None => String::from("-"),
+        };
+        let mut panel = column![
+            text(format!(
+                "Geweke |z| > 1.96: {} / {}   H-W not stationary: {}",
+                geweke_failed,
+                reports.len(),
+                not_stationary
+            )),
+            row![
+                text(format!("suggested burn-in {} draws ", burn_in)),
+                button("Apply burn-in").on_press(Message::ApplyBurnIn),
+            ],
+        ];
+        for index in self.stage.selected.iter() {
+            if let Some(report) = reports.get(*index) {
+                let start = |hw: Option<stationarity::HeidelbergerWelch>| match hw {
+                    Some(hw) if hw.stationary => format!("{}", hw.start),
+                    Some(_) => String::from("fail"),
+                    None => String::from("-"),
+                };
+                panel = panel.push(text(format!(
+                    "#{}  Geweke z ({}, {})  H-W start ({}, {})",
+                    index,
+                    format_z(report.geweke.0),
+                    format_z(report.geweke.1),
+                    start(report.heidelberger_welch.0),
+                    start(report.heidelberger_welch.1)
+                )));
+            }
+        }
+        panel.into()
+    }
+
+    /// The Gaussian the chains should converge to, if the current sampler has
+    /// one in closed form.
+    fn metrics_target(&self) -> Option<GaussianTarget> {
+        match self.sampler {
+            Sampler::ReversibleJump => None,
+            Sampler::Sgld | Sampler::Sghmc => {
+                let posterior = self.dataset.posterior();
+                Some(GaussianTarget {
+                    mean: posterior.mean,
+                    cov: posterior.cov,
+                })
+            }
+            // distances are only defined against a Gaussian reference.
+            _ if self.stage.target == Target::Painted => None,
+            _ => Some(GaussianTarget::independent(
+                (self.xmean as f64, self.ymean as f64),
+                (self.xstddev as f64, self.ystddev as f64),
+            )),
+        }
+    }
+
+    /// Where the density has its mass for samplers that do not explore the
+    /// stage's target.
+    fn sampled_domain(&self) -> Option<(Point, Point)> {
+        match self.sampler {
+            Sampler::Sgld | Sampler::Sghmc => {
+                let posterior = self.dataset.posterior();
+                let reach = (
+                    5.0 * posterior.cov[0][0].sqrt(),
+                    5.0 * posterior.cov[1][1].sqrt(),
+                );
+                Some((
+                    Point::new(
+                        (posterior.mean.0 - reach.0) as f32,
+                        (posterior.mean.1 - reach.1) as f32,
+                    ),
+                    Point::new(
+                        (posterior.mean.0 + reach.0) as f32,
+                        (posterior.mean.1 + reach.1) as f32,
+                    ),
+                ))
+            }
+            // both component means are drawn from the prior.
+            Sampler::ReversibleJump => {
+                let data = &self.rj.data;
+                let low = (data.prior_mean - 3.0 * data.prior_dev) as f32;
+                let high = (data.prior_mean + 3.0 * data.prior_dev) as f32;
+                Some((Point::new(low, low), Point::new(high, high)))
+            }
+            _ => None,
+        }
+    }
+
+    fn update_distance_plot(&mut self) {
+        let series = |label: &str, color: Color, value: fn(&Distances) -> f64| Series {
+            label: String::from(label),
+            color,
+            points: self
+                .distance_log
+                .iter()
+                .map(|(tick, distances)| (*tick as f64, value(distances)))
+                .collect(),
+        };
+        let plot = vec![
+            series("KS x", Color::from_rgb8(0x12, 0x93, 0xD8), |d| d.ks.0),
+            series("KS y", Color::from_rgb8(0xf4, 0xa2, 0x61), |d| d.ks.1),
+            series("SW2", Color::from_rgb8(0x2a, 0x9d, 0x8f), |d| {
+                d.sliced_wasserstein
+            }),
+            series("KL", Color::from_rgb8(0xe6, 0x39, 0x46), |d| d.kl),
+        ];
+        self.distance_plot.set_series(plot);
+    }
+
+    fn reset_sgld(&mut self) {
+        self.sgld.reset();
+        self.stage.overlay.clear();
+        if self.sampler == Sampler::Sgld || self.sampler == Sampler::Sghmc {
+            let posterior = self.dataset.posterior();
+            self.stage.overlay = [1.0, 2.0, 3.0]
+                .iter()
+                .map(|k| posterior.ellipse(*k, 64))
+                .collect();
+        }
+    }
+
+    fn sgld_panel(&self) -> Element<'_, Message, Renderer<Theme>> {
+        if self.sampler != Sampler::Sgld && self.sampler != Sampler::Sghmc {
+            return column![].into();
+        }
+        let posterior = self.dataset.posterior();
+        column![
+            text(format!(
+                "step {}   eps {:.5}   exact posterior mean ({:.3}, {:.3})   no MH correction",
+                self.sgld.iteration,
+                self.sgld.current_step_size(),
+                posterior.mean.0,
+                posterior.mean.1
+            )),
+            row![
+                text(format!("step size {:.4}", self.sgld.step_size)),
+                container(slider(
+                    1..=100,
+                    self.sgld_step_slider,
+                    Message::SgldStepChanged
+                ))
+                .width(200),
+                text(format!("decay {:.2}", self.sgld.decay)),
+                container(slider(
+                    0..=100,
+                    self.sgld_decay_slider,
+                    Message::SgldDecayChanged
+                ))
+                .width(200),
+                text(format!("batch {}", self.sgld.batch_size)),
+                container(slider(
+                    1..=DATASET_SIZE as u32,
+                    self.sgld_batch_slider,
+                    Message::SgldBatchChanged
+                ))
+                .width(200),
+            ],
+        ]
+        .into()
+    }
+
+    fn annealing_panel(&self) -> Element<'_, Message, Renderer<Theme>> {
+        if self.sampler != Sampler::Annealing {
+            return column![].into();
+        }
+        let best = match self.annealer.best {
+            Some((best, density)) => {
+                format!("best ({:.3}, {:.3}) density {:.3}", best.x, best.y, density)
+            }
+            None => String::from("best -"),
+        };
+        row![
+            text(format!(
+                "step {}   T {:.3}   {}",
+                self.annealer.iteration,
+                self.annealer.temperature(),
+                best
+            )),
+            pick_list(
+                &Schedule::ALL[..],
+                Some(self.annealer.schedule),
+                Message::ScheduleSelected
+            ),
+        ]
+        .into()
+    }
+
+    fn smc_panel(&self) -> Element<'_, Message, Renderer<Theme>> {
+        if self.sampler != Sampler::Smc {
+            return column![].into();
+        }
+        let events = self
+            .smc
+            .events
+            .iter()
+            .rev()
+            .take(5)
+            .map(|event| {
+                format!(
+                    "step {} (beta {:.2}, ESS {:.1})",
+                    event.iteration, event.beta, event.ess
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+        column![
+            row![
+                text(format!(
+                    "beta {:.2}   ESS {:.1} / {}   resamples {}",
+                    self.smc.beta,
+                    self.smc.ess(),
+                    self.smc.log_weights.len(),
+                    self.smc.events.len()
+                )),
+                pick_list(
+                    &Resampling::ALL[..],
+                    Some(self.smc.resampling),
+                    Message::ResamplingSelected
+                ),
+            ],
+            text(format!("recent resampling: {}", events)),
+        ]
+        .into()
+    }
+}
+
 impl Application for MetropolisVisualizer {
     type Executor = executor::Default;
     type Message = Message;
@@ -57,18 +966,17 @@ impl Application for MetropolisVisualizer {
     type Flags = ();
 
     fn new(_flags: Self::Flags) -> (Self, iced::Command<Self::Message>) {
+        let mixture =
+            MixtureData::synthetic(MIXTURE_SIZE, MIXTURE_WEIGHT, MIXTURE_MEANS, MIXTURE_NOISE);
+        let model_panel = ModelPanel::new(&mixture);
+        let (mean, stddev) = (
+            Point::new(X_MEAN as f32, Y_MEAN as f32),
+            Point::new(X_STDDEV as f32, Y_STDDEV as f32),
+        );
+        let init_strategy = Strategy::Overdispersed;
+        let setting = init_setting(Viewport::default(), mean, stddev, Vec::new());
         let emulator = MetropolisVisualizer {
-            stage: Stage::new(
-                Point {
-                    x: X_MEAN as f32,
-                    y: Y_MEAN as f32,
-                },
-                Point {
-                    x: X_STDDEV as f32,
-                    y: Y_STDDEV as f32,
-                },
-                100,
-            ),
+            stage: Stage::new(mean, stddev, 100, init_strategy, &setting),
             xmean: X_MEAN as f32,
             ymean: Y_MEAN as f32,
             xstddev: X_STDDEV as f32,
@@ -76,7 +984,50 @@ impl Application for MetropolisVisualizer {
             x_curve: BellCurve::new(X_MEAN, X_STDDEV),
             y_curve: BellCurve::new(Y_MEAN, Y_STDDEV),
             now: Instant::now(),
+            ticks: 0,
+            sampling_time: Duration::ZERO,
+            chain_summary: ChainSummary::default(),
+            computing_diagnostics: false,
+            diagnostics_generation: 0,
+            acf_plot: {
+                let mut plot = LinePlot::new("autocorrelation");
+                plot.y_range = Some((-0.2, 1.0));
+                plot
+            },
+            distance_log: Vec::new(),
+            distance_plot: {
+                let mut plot = LinePlot::new("distance to target");
+                plot.log_y = true;
+                plot
+            },
+            stationarity: Vec::new(),
+            expectations: vec![
+                Functional::Mean(Coordinate::X),
+                Functional::Mean(Coordinate::Y),
+            ],
+            expectation_log: vec![Vec::new(), Vec::new()],
+            mcse_method: McseMethod::BatchMeans,
+            functional_kind: FunctionalKind::Mean,
+            functional_coordinate: Coordinate::X,
+            functional_slider: 50,
+            plotted_expectation: None,
+            expectation_plot: LinePlot::new("running estimate +/- 2 MCSE"),
+            trace_x: LinePlot::new("x trace"),
+            trace_y: LinePlot::new("y trace"),
             is_playing: false,
+            batch_size: DEFAULT_BATCH_SIZE,
+            init_strategy,
+            previous_run: Vec::new(),
+            sampler: Sampler::Metropolis,
+            smc: Smc::new(100, SMC_DELTA),
+            annealer: Annealer::new(Schedule::Geometric, INITIAL_TEMPERATURE),
+            dataset: Dataset::synthetic(DATASET_SIZE, DATASET_WEIGHTS, DATASET_NOISE),
+            sgld: Sgld::new(0.005, 0.0, 5),
+            sgld_step_slider: 50,
+            sgld_decay_slider: 0,
+            sgld_batch_slider: 5,
+            rj: ReversibleJump::new(mixture, Vec::new()),
+            model_panel,
             speed: 100,
             x_mean_slider: 2,
             y_mean_slider: 2,
@@ -91,77 +1042,440 @@ impl Application for MetropolisVisualizer {
     }
 
     fn update(&mut self, message: Self::Message) -> iced::Command<Self::Message> {
-        let divisor = 50.0 / 2.0;
+        let mut command = Command::none();
+        let stepping = matches!(message, Message::Step);
         match message {
             Message::XMeanSliderChanged(val) => {
                 self.x_mean_slider = val;
                 self.xmean = val as f32 / 10.0;
-                self.stage.mean.x = self.xmean;
+                self.update_target();
             }
             Message::YMeanSliderChanged(val) => {
                 self.y_mean_slider = val;
                 self.ymean = val as f32 / 10.0;
-                self.stage.mean.y = self.ymean;
+                self.update_target();
             }
             Message::XStdDevSliderChanged(val) => {
                 self.x_stddev_slider = val;
                 self.xstddev = val as f32 / 50.0;
-                self.stage.stddev.x = self.xstddev;
+                self.update_target();
             }
             Message::YStdDevSliderChanged(val) => {
                 self.y_stddev_slider = val;
                 self.ystddev = val as f32 / 50.0;
-                self.stage.stddev.y = self.ystddev;
+                self.update_target();
+            }
+            Message::SamplerSelected(sampler) => {
+                self.sampler = sampler;
+                // the slider target is not what these samplers explore.
+                self.stage.set_show_target(!matches!(
+                    sampler,
+                    Sampler::Sgld | Sampler::Sghmc | Sampler::ReversibleJump
+                ));
+                for player in self.stage.players.iter_mut() {
+                    player.line = None;
+                    player.weight = 1.0;
+                }
+                self.stage.clear_history();
+                self.stage.set_sampled_domain(self.sampled_domain());
+                if sampler == Sampler::Smc {
+                    self.reset_smc();
+                }
+                self.reset_annealer();
+                self.reset_sgld();
+                self.reset_rj();
+                self.reset_diagnostics();
+            }
+            Message::ResamplingSelected(resampling) => {
+                self.smc.resampling = resampling;
+            }
+            Message::ScheduleSelected(schedule) => {
+                self.annealer.schedule = schedule;
+                self.reset_annealer();
+            }
+            Message::SgldStepChanged(val) => {
+                self.sgld_step_slider = val;
+                self.sgld.step_size = val as f64 / 10000.0;
+            }
+            Message::SgldDecayChanged(val) => {
+                self.sgld_decay_slider = val;
+                self.sgld.decay = val as f64 / 100.0;
+            }
+            Message::SgldBatchChanged(val) => {
+                self.sgld_batch_slider = val;
+                self.sgld.batch_size = val as usize;
+            }
+            Message::HeatmapSelected(mode) => {
+                self.stage.heatmap_mode = mode;
+            }
+            Message::TrailLengthChanged(val) => {
+                self.stage.trail_length = val as usize;
+                for player in self.stage.players.iter_mut() {
+                    while player.trail.len() > self.stage.trail_length {
+                        player.trail.pop_front();
+                    }
+                }
+            }
+            Message::Stage(StageEvent::PlayerClicked(index)) => {
+                self.stage.toggle_selected(index);
+                self.update_traces();
+            }
+            Message::Stage(StageEvent::ViewportChanged(viewport)) => {
+                self.stage.set_viewport(viewport);
+            }
+            Message::Stage(StageEvent::PlayerPlaced(position)) => {
+                self.stage.add_player(position);
+                self.players_changed();
+            }
+            Message::Stage(StageEvent::RegionPlaced(a, b)) => {
+                self.stage.place_uniform(a, b, self.batch_size as usize);
+                self.players_changed();
+            }
+            Message::ToolSelected(tool) => {
+                self.stage.tool = tool;
+                if tool == Tool::Paint && self.stage.target != Target::Painted {
+                    self.select_target(Target::Painted);
+                }
+            }
+            Message::Stage(StageEvent::Painted(position)) => {
+                self.stage.paint(position);
+            }
+            Message::TargetSelected(target) => {
+                self.select_target(target);
+            }
+            Message::BrushRadiusChanged(val) => {
+                self.stage.brush_radius = val as f32 / 20.0;
+            }
+            Message::ToggleErase => {
+                self.stage.erasing = !self.stage.erasing;
+            }
+            Message::SmoothPainting => {
+                self.stage.smooth_painting();
+            }
+            Message::ClearPainting => {
+                self.stage.clear_painting();
+            }
+            Message::BatchSizeChanged(val) => {
+                self.batch_size = val;
+            }
+            Message::StrategySelected(strategy) => {
+                self.init_strategy = strategy;
+            }
+            Message::ClearPlayers => {
+                self.stage.players.clear();
+                self.players_changed();
+            }
+            Message::ResetView => {
+                self.stage.set_viewport(Viewport::default());
+            }
+            // started before the last reset or change of expectations, so it
+            // describes another run or other functionals.
+            Message::DiagnosticsComputed(diagnostics)
+                if diagnostics.generation != self.diagnostics_generation =>
+            {
+                self.computing_diagnostics = false;
+            }
+            Message::DiagnosticsComputed(diagnostics) => {
+                self.computing_diagnostics = false;
+                self.update_diagnostics(diagnostics.summary);
+                if let Some(distances) = diagnostics.distances {
+                    self.distance_log.push((self.ticks, distances));
+                    self.update_distance_plot();
+                }
+                self.stationarity = diagnostics.stationarity;
+                for (log, estimate) in self
+                    .expectation_log
+                    .iter_mut()
+                    .zip(diagnostics.expectations)
+                {
+                    if let Some(estimate) = estimate {
+                        log.push((self.ticks, estimate));
+                    }
+                }
+                self.update_expectation_plot();
+            }
+            Message::McseMethodSelected(method) => {
+                self.mcse_method = method;
+                self.diagnostics_generation += 1;
+                self.expectation_log.iter_mut().for_each(|log| log.clear());
+                self.update_expectation_plot();
+            }
+            Message::FunctionalKindSelected(kind) => {
+                self.functional_kind = kind;
+            }
+            Message::CoordinateSelected(coordinate) => {
+                self.functional_coordinate = coordinate;
+            }
+            Message::FunctionalSliderChanged(val) => {
+                self.functional_slider = val;
+            }
+            Message::AddExpectation => {
+                self.expectations.push(self.new_functional());
+                self.expectation_log.push(Vec::new());
+                self.diagnostics_generation += 1;
+            }
+            Message::RemoveExpectation(index) => {
+                self.expectations.remove(index);
+                self.expectation_log.remove(index);
+                self.diagnostics_generation += 1;
+                self.plotted_expectation = match self.plotted_expectation {
+                    Some(plotted) if plotted == index => None,
+                    Some(plotted) if plotted > index => Some(plotted - 1),
+                    plotted => plotted,
+                };
+                self.update_expectation_plot();
+            }
+            Message::PlotExpectation(index) => {
+                self.plotted_expectation = Some(index);
+                self.update_expectation_plot();
+            }
+            Message::ApplyBurnIn => {
+                self.stage
+                    .apply_burn_in(stationarity::suggest_burn_in(&self.stationarity));
+                // results in flight and logged so far include the discarded draws.
+                self.diagnostics_generation += 1;
+                self.stationarity.clear();
+                self.distance_log.clear();
+                self.update_distance_plot();
+                self.expectation_log.iter_mut().for_each(|log| log.clear());
+                self.update_expectation_plot();
+                self.update_traces();
             }
             Message::Toggle => {
                 self.is_playing = !self.is_playing;
             }
             Message::Reset => {
-                self.x_curve.position = Point { x: 0.0, y: 0.0 };
-                self.stage.players = self
-                    .stage
-                    .players
-                    .iter()
-                    .map(|_| Player::default())
-                    .collect();
-            }
-            Message::Run(_) => {
-                for player in self.stage.players.iter_mut() {
-                    let x_pos = player.current.x / 10.0 / divisor;
-                    let y_pos = player.current.y / 10.0 / divisor;
-                    if self.now.elapsed().as_millis() >= SPEED {
-                        player.candidate = metropolis::derive_candidate_2d(
-                            (self.xmean as f64, self.ymean as f64),
-                            (X_STDDEV, Y_STDDEV),
-                            Point { x: x_pos, y: y_pos },
-                        );
-                        player.candidate.position = Point {
-                            x: player.candidate.position.x * 10.0 * divisor,
-                            y: player.candidate.position.y * 10.0 * divisor,
+                self.x_curve.position = 0.0;
+                self.previous_run = self.stage.players.iter().map(|p| p.current).collect();
+                self.stage.set_players(init::positions(
+                    self.init_strategy,
+                    self.stage.players.len(),
+                    &self.init_setting(),
+                ));
+                self.stage.clear_history();
+                if self.sampler == Sampler::Smc {
+                    self.reset_smc();
+                }
+                self.reset_annealer();
+                self.reset_sgld();
+                self.reset_rj();
+                self.reset_diagnostics();
+            }
+            Message::Run(_) | Message::Step => {
+                if stepping {
+                    self.is_playing = false;
+                }
+                let started = Instant::now();
+                let tick = stepping || self.now.elapsed().as_millis() >= SPEED;
+                // in step mode the first selected Metropolis chain is explained.
+                let explained = self.stage.selected.first().copied().filter(|_| {
+                    stepping
+                        && self.sampler == Sampler::Metropolis
+                        && self.stage.target == Target::Gaussian
+                });
+                let mut explanation = None;
+                let before: Vec<Point> = self.stage.players.iter().map(|p| p.current).collect();
+                let mut accepted: Vec<Option<bool>> = vec![None; before.len()];
+                if self.sampler == Sampler::ReversibleJump && tick {
+                    accepted = self.rj.step().into_iter().map(Some).collect();
+                    for (player, model) in self.stage.players.iter_mut().zip(self.rj.chains.iter())
+                    {
+                        player.current = model.position();
+                    }
+                    self.model_panel.summary = self.rj.summary;
+                    self.model_panel.redraw();
+                }
+                if self.sampler == Sampler::Smc && tick {
+                    let mean = (self.xmean as f64, self.ymean as f64);
+                    let dev = (self.xstddev as f64, self.ystddev as f64);
+                    let painted = &self.stage.painted;
+                    let target = self.stage.target;
+                    let mut positions: Vec<Point> =
+                        self.stage.players.iter().map(|p| p.current).collect();
+                    let moves = self.smc.step(
+                        &mut positions,
+                        |p| {
+                            gaussian::log_density_2d(
+                                (PRIOR_MEAN, PRIOR_MEAN),
+                                (PRIOR_STDDEV, PRIOR_STDDEV),
+                                p.x as f64,
+                                p.y as f64,
+                            )
+                        },
+                        |p| match target {
+                            Target::Gaussian => {
+                                gaussian::log_density_2d(mean, dev, p.x as f64, p.y as f64)
+                            }
+                            Target::Painted => painted.log_density(p),
+                        },
+                    );
+                    accepted = moves.into_iter().map(Some).collect();
+                    let particles = positions.len() as f64;
+                    for ((player, position), weight) in self
+                        .stage
+                        .players
+                        .iter_mut()
+                        .zip(positions)
+                        .zip(self.smc.weights())
+                    {
+                        player.current = position;
+                        player.weight = (weight * particles) as f32;
+                    }
+                }
+                for (chain, player) in self.stage.players.iter_mut().enumerate() {
+                    let position = player.current;
+                    if tick {
+                        let mean = (self.xmean as f64, self.ymean as f64);
+                        let dev = (self.xstddev as f64, self.ystddev as f64);
+                        let painted = &self.stage.painted;
+                        let target = self.stage.target;
+                        let density = |p: Point| match target {
+                            Target::Gaussian => {
+                                gaussian::density_2d(mean, dev, p.x as f64, p.y as f64)
+                            }
+                            Target::Painted => painted.density(p),
+                        };
+                        let log_density = |p: Point| match target {
+                            Target::Gaussian => {
+                                gaussian::log_density_2d(mean, dev, p.x as f64, p.y as f64)
+                            }
+                            Target::Painted => painted.log_density(p),
+                        };
+                        accepted[chain] = match self.sampler {
+                            Sampler::Metropolis if explained == Some(chain) => {
+                                let step = metropolis::explained_step_2d(mean, dev, position);
+                                player.candidate = Candidate::new(step.acceptance, step.candidate);
+                                player.current = step.next;
+                                explanation = Some((chain, step));
+                                let (x, y) = step.accepted();
+                                Some(x || y)
+                            }
+                            Sampler::Metropolis if target == Target::Painted => {
+                                player.candidate =
+                                    metropolis::derive_joint_candidate_2d(density, position, 1.0);
+                                let (next, moved) =
+                                    metropolis::metropolis_step_2d(position, &player.candidate);
+                                player.current = next;
+                                Some(moved)
+                            }
+                            Sampler::Metropolis => {
+                                player.candidate =
+                                    metropolis::derive_candidate_2d(mean, dev, position);
+                                let (next, moved) =
+                                    metropolis::metropolis_step_2d(position, &player.candidate);
+                                player.current = next;
+                                Some(moved)
+                            }
+                            Sampler::Annealing => {
+                                player.candidate = match target {
+                                    Target::Gaussian => metropolis::derive_tempered_candidate_2d(
+                                        mean,
+                                        dev,
+                                        position,
+                                        self.annealer.beta(),
+                                    ),
+                                    Target::Painted => metropolis::derive_joint_candidate_2d(
+                                        density,
+                                        position,
+                                        self.annealer.beta(),
+                                    ),
+                                };
+                                let (next, moved) =
+                                    metropolis::metropolis_step_2d(position, &player.candidate);
+                                self.annealer.observe(next, density(next));
+                                player.current = next;
+                                Some(moved)
+                            }
+                            Sampler::Sgld => {
+                                player.current = self.sgld.sgld_step(&self.dataset, position);
+                                None
+                            }
+                            Sampler::Sghmc => {
+                                player.current =
+                                    self.sgld.sghmc_step(&self.dataset, chain, position);
+                                None
+                            }
+                            // already stepped above, all chains at once.
+                            Sampler::Smc | Sampler::ReversibleJump => accepted[chain],
+                            Sampler::HitAndRun | Sampler::RandomDirection => {
+                                let step = if self.sampler == Sampler::HitAndRun {
+                                    hitandrun::slice_step_2d(log_density, position, LINE_WIDTH)
+                                } else {
+                                    hitandrun::random_direction_step_2d(
+                                        log_density,
+                                        position,
+                                        LINE_SCALE,
+                                    )
+                                };
+                                player.current = step.position;
+                                player.line = Some(step.line);
+                                Some(step.accepted)
+                            }
                         };
-                        player.current =
-                            metropolis::metropolis_state_2d(player.current, &player.candidate);
                     }
                 }
-                let x64 = self.x_curve.position.x as f64;
-                self.x_curve.position = Point {
-                    x: self.x_curve.position.x + 1.0,
-                    y: (gaussian::distribution_density(
-                        X_MEAN,
-                        Y_STDDEV,
-                        (x64 / 10.0) / divisor as f64,
-                    ) * 100.0) as f32
-                        + 5.0,
-                };
-                if self.now.elapsed().as_millis() >= SPEED {
+                self.x_curve.position += CURVE_STEP;
+                if tick {
+                    self.stage.explanation = explanation;
+                    self.record_history(&before, &accepted);
+                    self.sampling_time += started.elapsed();
+                    self.update_traces();
+                    self.ticks += 1;
+                    if self.ticks % DIAGNOSTICS_EVERY == 0 && !self.computing_diagnostics {
+                        // summaries are expensive, compute them off the UI thread.
+                        self.computing_diagnostics = true;
+                        let (xs, ys) = self.stage.chains();
+                        let target = self.metrics_target();
+                        let functionals = self.expectations.clone();
+                        let method = self.mcse_method;
+                        let generation = self.diagnostics_generation;
+                        command = Command::perform(
+                            async move {
+                                let summary = diagnostics::summarize(&xs, &ys, ACF_LAGS);
+                                let samples: Vec<Point> = xs
+                                    .iter()
+                                    .flatten()
+                                    .zip(ys.iter().flatten())
+                                    .map(|(x, y)| Point::new(*x as f32, *y as f32))
+                                    .collect();
+                                let distances =
+                                    target.and_then(|target| metrics::distances(&samples, &target));
+                                let stationarity = xs
+                                    .iter()
+                                    .zip(ys.iter())
+                                    .map(|(x, y)| stationarity::report(x, y))
+                                    .collect();
+                                let expectations = functionals
+                                    .iter()
+                                    .map(|f| expectation::estimate(f, &xs, &ys, method))
+                                    .collect();
+                                Box::new(Diagnostics {
+                                    generation,
+                                    summary,
+                                    distances,
+                                    stationarity,
+                                    expectations,
+                                })
+                            },
+                            Message::DiagnosticsComputed,
+                        );
+                    }
+                    if self.sampler == Sampler::Sgld || self.sampler == Sampler::Sghmc {
+                        self.sgld.advance();
+                    }
+                    if self.sampler == Sampler::Annealing {
+                        self.annealer.advance();
+                        self.stage.best = self.annealer.best.map(|(best, _)| best);
+                    }
                     self.now = Instant::now();
                 }
             }
         }
+        self.update_log_densities();
         self.stage.redraw();
         self.x_curve.redraw();
         self.y_curve.redraw();
-        Command::none()
+        command
     }
 
     fn view(&self) -> Element<'_, Self::Message, Renderer<Self::Theme>> {
@@ -190,8 +1504,45 @@ impl Application for MetropolisVisualizer {
         column![
             row![
                 button("Toggle").on_press(Message::Toggle),
+                tooltip(
+                    button("Step").on_press(Message::Step),
+                    "Advance one iteration. With Metropolis, select a player to see its decision.",
+                    tooltip::Position::Bottom
+                ),
                 button("Reset").on_press(Message::Reset),
+                pick_list(
+                    &Strategy::ALL[..],
+                    Some(self.init_strategy),
+                    Message::StrategySelected
+                ),
+                button("Reset view").on_press(Message::ResetView),
+                pick_list(
+                    &Sampler::ALL[..],
+                    Some(self.sampler),
+                    Message::SamplerSelected
+                ),
+                pick_list(
+                    &HeatmapMode::ALL[..],
+                    Some(self.stage.heatmap_mode),
+                    Message::HeatmapSelected
+                ),
+                pick_list(&Tool::ALL[..], Some(self.stage.tool), Message::ToolSelected),
+                text(format!(" batch {} ", self.batch_size)),
+                container(slider(1..=100, self.batch_size, Message::BatchSizeChanged)).width(100),
+                button("Clear players").on_press(Message::ClearPlayers),
+                text(format!(" trail {} ", self.stage.trail_length)),
+                container(slider(
+                    0..=100,
+                    self.stage.trail_length as u32,
+                    Message::TrailLengthChanged
+                ))
+                .width(150),
             ],
+            self.acceptance_panel(),
+            self.smc_panel(),
+            self.annealing_panel(),
+            self.sgld_panel(),
+            self.paint_panel(),
             row![
                 text(format!("{}", self.stage.mean.x)),
                 container(x_mean_slider).width(Length::Fill).center_x(),
@@ -211,9 +1562,7 @@ impl Application for MetropolisVisualizer {
             // Canvas::new(&self.y_curve)
             //     .height(Length::Fill)
             //     .width(Length::Fill),
-            Canvas::new(&self.stage)
-                .height(Length::Fill)
-                .width(Length::Fill) // ]
+            self.stage_view(),
         ]
         .into()
     }
@@ -240,3 +1589,22 @@ impl Application for MetropolisVisualizer {
         }
     }
 }
+
+/// What the initialisation strategies start new players from, given the view,
+/// the target and where the previous run ended.
+fn init_setting(
+    viewport: Viewport,
+    target_mean: Point,
+    target_stddev: Point,
+    previous: Vec<Point>,
+) -> init::Setting {
+    init::Setting {
+        point: viewport.center(),
+        target_mean,
+        target_stddev,
+        region: (viewport.min, viewport.max),
+        prior_mean: PRIOR,
+        prior_stddev: PRIOR_SPREAD,
+        previous,
+    }
+}
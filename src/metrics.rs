@@ -0,0 +1,185 @@
+/**
+ * Discrepancies between the draws the chains produced and a known Gaussian
+ * target: Kolmogorov-Smirnov statistics of the marginals, the sliced
+ * 2-Wasserstein distance and the KL divergence of the 2D sample histogram,
+ * all computed from the same draws.
+ */
+use crate::gaussian;
+use crate::histogram::Histogram2d;
+
+use iced::Point;
+use std::f64::consts::PI;
+
+// floor on the target cell probability so the KL stays finite.
+const KL_EPSILON: f64 = 1e-12;
+// the KL histogram covers this many std. devs. either side of the target mean
+// with this many cells per axis.
+const KL_REACH: f64 = 5.0;
+const KL_BINS: usize = 60;
+
+/// A 2D Gaussian target with full covariance.
+#[derive(Debug, Clone, Copy)]
+pub struct GaussianTarget {
+    pub mean: (f64, f64),
+    pub cov: [[f64; 2]; 2],
+}
+
+impl GaussianTarget {
+    /// Independent coordinates, as used by the stage sliders.
+    pub fn independent(mean: (f64, f64), dev: (f64, f64)) -> Self {
+        GaussianTarget {
+            mean,
+            cov: [[dev.0 * dev.0, 0.0], [0.0, dev.1 * dev.1]],
+        }
+    }
+
+    pub fn density(&self, x: f64, y: f64) -> f64 {
+        let det = self.cov[0][0] * self.cov[1][1] - self.cov[0][1] * self.cov[1][0];
+        let (dx, dy) = (x - self.mean.0, y - self.mean.1);
+        let q = (self.cov[1][1] * dx * dx - 2.0 * self.cov[0][1] * dx * dy
+            + self.cov[0][0] * dy * dy)
+            / det;
+        (-0.5 * q).exp() / (2.0 * PI * det.sqrt())
+    }
+
+    /// Mean and std. dev. of the target projected onto `direction`.
+    fn projection(&self, direction: (f64, f64)) -> (f64, f64) {
+        let (u, v) = direction;
+        let mean = u * self.mean.0 + v * self.mean.1;
+        let var = u * u * self.cov[0][0] + 2.0 * u * v * self.cov[0][1] + v * v * self.cov[1][1];
+        (mean, var.sqrt())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Distances {
+    pub ks: (f64, f64),
+    pub sliced_wasserstein: f64,
+    pub kl: f64,
+}
+
+/// Kolmogorov-Smirnov statistic sup |F_n - F| of `samples` against `cdf`.
+pub fn ks_statistic<F>(samples: &[f64], cdf: F) -> f64
+where
+    F: Fn(f64) -> f64,
+{
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let n = sorted.len() as f64;
+    sorted
+        .iter()
+        .enumerate()
+        .map(|(i, x)| {
+            let f = cdf(*x);
+            f64::max(f - i as f64 / n, (i + 1) as f64 / n - f)
+        })
+        .fold(0.0, f64::max)
+}
+
+/// 2-Wasserstein distance between 1D `samples` and N(mean, dev), matching
+/// the sorted samples with the target quantiles. `None` without samples.
+pub fn wasserstein_1d(samples: &[f64], mean: f64, dev: f64) -> Option<f64> {
+    if samples.is_empty() {
+        return None;
+    }
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let n = sorted.len() as f64;
+    let sum: f64 = sorted
+        .iter()
+        .enumerate()
+        .map(|(i, x)| {
+            let q = mean + dev * gaussian::inverse_cdf((i as f64 + 0.5) / n);
+            (x - q) * (x - q)
+        })
+        .sum();
+    Some((sum / n).sqrt())
+}
+
+/// Sliced 2-Wasserstein distance over `directions` evenly spaced angles.
+/// `None` without samples or directions.
+pub fn sliced_wasserstein(
+    samples: &[Point],
+    target: &GaussianTarget,
+    directions: usize,
+) -> Option<f64> {
+    if directions == 0 {
+        return None;
+    }
+    let sum: f64 = (0..directions)
+        .map(|k| {
+            let theta = PI * k as f64 / directions as f64;
+            let direction = (theta.cos(), theta.sin());
+            let projected: Vec<f64> = samples
+                .iter()
+                .map(|p| direction.0 * p.x as f64 + direction.1 * p.y as f64)
+                .collect();
+            let (mean, dev) = target.projection(direction);
+            wasserstein_1d(&projected, mean, dev).map(|w| w * w)
+        })
+        .sum::<Option<f64>>()?;
+    Some((sum / directions as f64).sqrt())
+}
+
+/// KL(samples || target) between the histogram cell probabilities and the
+/// target density integrated (midpoint rule) over the same cells.
+pub fn histogram_kl<F>(histogram: &Histogram2d, density: F) -> f64
+where
+    F: Fn(f64, f64) -> f64,
+{
+    let binned: usize = (0..histogram.y.bins())
+        .flat_map(|j| (0..histogram.x.bins()).map(move |i| (i, j)))
+        .map(|(i, j)| histogram.count(i, j))
+        .sum();
+    if binned == 0 {
+        return 0.0;
+    }
+    let area = histogram.x.bin_width() * histogram.y.bin_width();
+    let mut kl = 0.0;
+    for j in 0..histogram.y.bins() {
+        for i in 0..histogram.x.bins() {
+            let count = histogram.count(i, j);
+            if count == 0 {
+                continue;
+            }
+            let p = count as f64 / binned as f64;
+            let x = histogram.x.bin_start(i) + histogram.x.bin_width() / 2.0;
+            let y = histogram.y.bin_start(j) + histogram.y.bin_width() / 2.0;
+            let q = (density(x, y) * area).max(KL_EPSILON);
+            kl += p * (p / q).ln();
+        }
+    }
+    kl
+}
+
+/// All the distances of `samples` to `target`, `None` without samples.
+pub fn distances(samples: &[Point], target: &GaussianTarget) -> Option<Distances> {
+    if samples.is_empty() {
+        return None;
+    }
+    let xs: Vec<f64> = samples.iter().map(|p| p.x as f64).collect();
+    let ys: Vec<f64> = samples.iter().map(|p| p.y as f64).collect();
+    let (x_dev, y_dev) = (target.cov[0][0].sqrt(), target.cov[1][1].sqrt());
+    let mut histogram = Histogram2d::new(
+        (
+            target.mean.0 - KL_REACH * x_dev,
+            target.mean.0 + KL_REACH * x_dev,
+        ),
+        (
+            target.mean.1 - KL_REACH * y_dev,
+            target.mean.1 + KL_REACH * y_dev,
+        ),
+        (KL_BINS, KL_BINS),
+    );
+    for (x, y) in xs.iter().zip(ys.iter()) {
+        histogram.add(*x, *y);
+    }
+    Some(Distances {
+        ks: (
+            ks_statistic(&xs, |x| gaussian::cdf(target.mean.0, x_dev, x)),
+            ks_statistic(&ys, |y| gaussian::cdf(target.mean.1, y_dev, y)),
+        ),
+        sliced_wasserstein: sliced_wasserstein(samples, target, 16)?,
+        kl: histogram_kl(&histogram, |x, y| target.density(x, y)),
+    })
+}
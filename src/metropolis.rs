@@ -3,6 +3,9 @@ use crate::gaussian;
 use iced::Point;
 use rand::Rng;
 
+// std. dev. of the random walk proposal on each coordinate.
+pub const PROPOSAL_STDDEV: f64 = 0.2;
+
 pub struct Candidate {
     prob_accept: (f64, f64),
     pub position: Point,
@@ -39,6 +42,19 @@ pub fn acceptance_2d(
     (x, y)
 }
 
+/// Acceptance on the target raised to the inverse temperature `beta`, as used
+/// by simulated annealing. `beta = 1.0` is plain Metropolis.
+pub fn tempered_acceptance_2d(
+    mean: (f64, f64),
+    dev: (f64, f64),
+    current: Point,
+    candidate: Point,
+    beta: f64,
+) -> (f64, f64) {
+    let (x, y) = acceptance_2d(mean, dev, current, candidate);
+    (x.powf(beta), y.powf(beta))
+}
+
 pub fn metropolis() {
     /* CAUTION project assumes
      * the PDF provided is proportional to the Gaussian Distribution!!
@@ -79,11 +95,16 @@ pub fn metropolis() {
 //     }
 // }
 
+/// Random walk proposal around `position` shared by the Metropolis samplers.
+pub fn propose_2d(position: Point) -> Point {
+    Point {
+        x: gaussian::sample_custom(position.x as f64, PROPOSAL_STDDEV) as f32,
+        y: gaussian::sample_custom(position.y as f64, PROPOSAL_STDDEV) as f32,
+    }
+}
+
 pub fn derive_candidate_2d(mean: (f64, f64), dev: (f64, f64), position: Point) -> Candidate {
-    let candidate_position = Point {
-        x: gaussian::sample_custom(position.x as f64, 0.2) as f32,
-        y: gaussian::sample_custom(position.y as f64, 0.2) as f32,
-    };
+    let candidate_position = propose_2d(position);
     let prob_accept = acceptance_2d(mean, dev, position, candidate_position);
     Candidate {
         position: candidate_position,
@@ -91,6 +112,82 @@ pub fn derive_candidate_2d(mean: (f64, f64), dev: (f64, f64), position: Point) -
     }
 }
 
+/// Candidate from the same random walk as `derive_candidate_2d`, accepted or
+/// rejected as a whole under an arbitrary unnormalised `density` raised to the
+/// inverse temperature `beta`.
+pub fn derive_joint_candidate_2d<F>(density: F, position: Point, beta: f64) -> Candidate
+where
+    F: Fn(Point) -> f64,
+{
+    let candidate_position = propose_2d(position);
+    let ratio = f64::min(density(candidate_position) / density(position), 1.0);
+    let prob_accept = ratio.powf(beta);
+    // the same probability for both coordinates moves them together.
+    Candidate {
+        position: candidate_position,
+        prob_accept: (prob_accept, prob_accept),
+    }
+}
+
+/// Everything that went into one Metropolis step, kept so it can be shown.
+#[derive(Debug, Clone, Copy)]
+pub struct Explanation {
+    pub current: Point,
+    pub candidate: Point,
+    // target density of each coordinate at the current point and candidate.
+    pub current_density: (f64, f64),
+    pub candidate_density: (f64, f64),
+    // acceptance probability of each coordinate, from `acceptance_2d`.
+    pub acceptance: (f64, f64),
+    pub uniform: f64,
+    pub next: Point,
+}
+
+impl Explanation {
+    pub fn accepted(&self) -> (bool, bool) {
+        (
+            self.acceptance.0 > self.uniform,
+            self.acceptance.1 > self.uniform,
+        )
+    }
+}
+
+/// One Metropolis step like `derive_candidate_2d` followed by
+/// `metropolis_state_2d`, returning the intermediate values as well.
+pub fn explained_step_2d(mean: (f64, f64), dev: (f64, f64), position: Point) -> Explanation {
+    let candidate = derive_candidate_2d(mean, dev, position);
+    let uniform: f64 = rand::thread_rng().gen();
+    let density = |p: Point| {
+        (
+            gaussian::distribution_density(mean.0, dev.0, p.x as f64),
+            gaussian::distribution_density(mean.1, dev.1, p.y as f64),
+        )
+    };
+    Explanation {
+        current: position,
+        candidate: candidate.position,
+        current_density: density(position),
+        candidate_density: density(candidate.position),
+        acceptance: candidate.prob_accept,
+        uniform,
+        next: decide_2d(position, &candidate, uniform),
+    }
+}
+
+pub fn derive_tempered_candidate_2d(
+    mean: (f64, f64),
+    dev: (f64, f64),
+    position: Point,
+    beta: f64,
+) -> Candidate {
+    let candidate_position = propose_2d(position);
+    let prob_accept = tempered_acceptance_2d(mean, dev, position, candidate_position, beta);
+    Candidate {
+        position: candidate_position,
+        prob_accept,
+    }
+}
+
 // pub fn metropolis_state(mean: f64, position: f64, candidate: &Candidate) -> f64 {
 //     /* CAUTION project assumes
 //      * the PDF provided is proportional to the Gaussian Distribution!!
@@ -111,7 +208,20 @@ pub fn derive_candidate_2d(mean: (f64, f64), dev: (f64, f64), position: Point) -
 
 pub fn metropolis_state_2d(position: Point, candidate: &Candidate) -> Point {
     let mut rng = rand::thread_rng();
-    let gen: f64 = rng.gen();
+    decide_2d(position, candidate, rng.gen())
+}
+
+/// `metropolis_state_2d` that also tells whether the candidate was accepted
+/// for at least one coordinate.
+pub fn metropolis_step_2d(position: Point, candidate: &Candidate) -> (Point, bool) {
+    let gen: f64 = rand::thread_rng().gen();
+    let accepted = candidate.prob_accept.0 > gen || candidate.prob_accept.1 > gen;
+    (decide_2d(position, candidate, gen), accepted)
+}
+
+/// Moves each coordinate of `position` to the candidate's if its acceptance
+/// probability beats the uniform draw `gen`.
+pub fn decide_2d(position: Point, candidate: &Candidate, gen: f64) -> Point {
     let mut pos = position;
     if candidate.prob_accept.0 > gen {
         pos.x = candidate.position.x;
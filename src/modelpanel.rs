@@ -0,0 +1,166 @@
+/**
+ * Side panel for the reversible-jump sampler: the posterior probability of
+ * each model as bars, and below it the data histogram with the posterior mean
+ * fit of both models.
+ */
+use crate::rjmcmc::{MixtureData, Model, Summary};
+
+use iced::widget::canvas::{self, stroke, Cache, Stroke};
+use iced::{Color, Point, Size, Theme};
+
+const BINS: usize = 30;
+
+pub struct ModelPanel {
+    pub summary: Summary,
+    data: Vec<f64>,
+    noise: f64,
+    cache: Cache,
+}
+
+impl ModelPanel {
+    pub fn new(data: &MixtureData) -> Self {
+        Self {
+            summary: Summary::default(),
+            data: data.y.clone(),
+            noise: data.noise,
+            cache: canvas::Cache::default(),
+        }
+    }
+
+    pub fn redraw(&mut self) {
+        self.cache.clear();
+    }
+}
+
+impl<Message> canvas::Program<Message> for ModelPanel {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        _theme: &Theme,
+        bounds: iced::Rectangle,
+        _cursor: canvas::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let geom = self.cache.draw(bounds.size(), |frame| {
+            let bar_height = bounds.height * 0.35;
+            let bar_width = bounds.width / 4.0;
+            for components in 1..=2 {
+                let probability = self.summary.probability(components) as f32;
+                let left = bar_width * (2 * components - 1) as f32 - bar_width / 2.0;
+                frame.fill_rectangle(
+                    Point {
+                        x: left,
+                        y: 20.0 + bar_height * (1.0 - probability),
+                    },
+                    Size {
+                        width: bar_width,
+                        height: bar_height * probability,
+                    },
+                    Color::from_rgb8(0x12, 0x93, 0xD8),
+                );
+                frame.fill_text(canvas::Text {
+                    content: format!("k={}: {:.2}", components, probability),
+                    position: Point {
+                        x: left,
+                        y: 25.0 + bar_height,
+                    },
+                    color: Color::WHITE,
+                    ..canvas::Text::default()
+                });
+            }
+
+            let summary = &self.summary;
+            frame.fill_text(canvas::Text {
+                content: format!(
+                    "k=1 mean {:.2}   k=2 means ({:.2}, {:.2}) weight {:.2}",
+                    summary.one_mean, summary.two_means.0, summary.two_means.1, summary.two_weight
+                ),
+                position: Point {
+                    x: 10.0,
+                    y: 50.0 + bar_height,
+                },
+                color: Color::WHITE,
+                size: 14.0,
+                ..canvas::Text::default()
+            });
+
+            // data histogram with the fitted densities on top.
+            let min = self.data.iter().cloned().fold(f64::INFINITY, f64::min);
+            let max = self.data.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+            let bin_width = (max - min) / BINS as f64;
+            let mut counts = [0usize; BINS];
+            for y in self.data.iter() {
+                let bin = (((y - min) / bin_width) as usize).min(BINS - 1);
+                counts[bin] += 1;
+            }
+            let base = bounds.height - 10.0;
+            let plot_height = bounds.height * 0.4;
+            // histogram is normalised to a density so the fits share its scale.
+            let to_density = |count: usize| count as f64 / (self.data.len() as f64 * bin_width);
+            let peak = to_density(counts.iter().cloned().max().unwrap_or(1));
+            let density_scale = plot_height / peak as f32;
+            let column = bounds.width / BINS as f32;
+            for (i, count) in counts.iter().enumerate() {
+                let density = to_density(*count);
+                let height = density as f32 * density_scale;
+                frame.fill_rectangle(
+                    Point {
+                        x: i as f32 * column,
+                        y: base - height,
+                    },
+                    Size {
+                        width: column - 1.0,
+                        height,
+                    },
+                    Color::from_rgb8(0x55, 0x55, 0x55),
+                );
+            }
+
+            let fits = [
+                (
+                    Model::One {
+                        mean: summary.one_mean,
+                    },
+                    Color::from_rgb8(0xf4, 0xa2, 0x61),
+                    summary.visits[0],
+                ),
+                (
+                    Model::Two {
+                        weight: summary.two_weight,
+                        means: summary.two_means,
+                    },
+                    Color::from_rgb8(0x2a, 0x9d, 0x8f),
+                    summary.visits[1],
+                ),
+            ];
+            for (model, color, visits) in fits.iter() {
+                if *visits == 0 {
+                    continue;
+                }
+                frame.stroke(
+                    &canvas::Path::new(|path| {
+                        for i in 0..=100 {
+                            let y = min + (max - min) * i as f64 / 100.0;
+                            let point = Point {
+                                x: bounds.width * i as f32 / 100.0,
+                                y: base - model.density(self.noise, y) as f32 * density_scale,
+                            };
+                            if i == 0 {
+                                path.move_to(point);
+                            } else {
+                                path.line_to(point);
+                            }
+                        }
+                    }),
+                    canvas::Stroke {
+                        width: 2.0,
+                        style: stroke::Style::Solid(*color),
+                        ..Stroke::default()
+                    },
+                );
+            }
+        });
+        vec![geom]
+    }
+}
@@ -0,0 +1,148 @@
+/**
+ * A target density painted by hand onto a grid.
+ */
+use iced::Point;
+
+// density everywhere the user has not painted, so acceptance ratios stay
+// defined.
+pub const BACKGROUND: f64 = 1e-9;
+
+/// Unnormalised density stored at the nodes of a regular grid over the
+/// rectangle from `min` to `max` and read back with bilinear interpolation.
+#[derive(Debug, Clone)]
+pub struct GridDensity {
+    pub min: Point,
+    pub max: Point,
+    columns: usize,
+    rows: usize,
+    values: Vec<f64>,
+}
+
+impl GridDensity {
+    pub fn new(min: Point, max: Point, columns: usize, rows: usize) -> Self {
+        // interpolation needs at least one cell between nodes on each axis.
+        assert!(columns >= 2 && rows >= 2, "grid needs at least 2x2 nodes");
+        Self {
+            min,
+            max,
+            columns,
+            rows,
+            values: vec![0.0; columns * rows],
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.values.iter_mut().for_each(|v| *v = 0.0);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.iter().all(|v| *v == 0.0)
+    }
+
+    fn step(&self) -> (f32, f32) {
+        (
+            (self.max.x - self.min.x) / (self.columns - 1) as f32,
+            (self.max.y - self.min.y) / (self.rows - 1) as f32,
+        )
+    }
+
+    fn node(&self, i: usize, j: usize) -> Point {
+        let (dx, dy) = self.step();
+        Point::new(self.min.x + i as f32 * dx, self.min.y + j as f32 * dy)
+    }
+
+    /// Adds a Gaussian bump of height `amount` and width `radius / 2` at
+    /// `center`, cut off at `radius`. A negative amount erases; values never
+    /// drop below zero.
+    pub fn brush(&mut self, center: Point, radius: f32, amount: f64) {
+        let sigma = (radius / 2.0) as f64;
+        for j in 0..self.rows {
+            for i in 0..self.columns {
+                let distance = self.node(i, j).distance(center);
+                if distance > radius {
+                    continue;
+                }
+                let falloff = (-0.5 * (distance as f64 / sigma).powi(2)).exp();
+                let value = &mut self.values[j * self.columns + i];
+                *value = (*value + amount * falloff).max(0.0);
+            }
+        }
+    }
+
+    /// One pass of a 3x3 box blur.
+    pub fn smooth(&mut self) {
+        let mut smoothed = vec![0.0; self.values.len()];
+        for j in 0..self.rows {
+            for i in 0..self.columns {
+                let (mut sum, mut count) = (0.0, 0.0);
+                for nj in j.saturating_sub(1)..=(j + 1).min(self.rows - 1) {
+                    for ni in i.saturating_sub(1)..=(i + 1).min(self.columns - 1) {
+                        sum += self.values[nj * self.columns + ni];
+                        count += 1.0;
+                    }
+                }
+                smoothed[j * self.columns + i] = sum / count;
+            }
+        }
+        self.values = smoothed;
+    }
+
+    /// Interpolated density at `position`, `BACKGROUND` outside the grid.
+    pub fn density(&self, position: Point) -> f64 {
+        let (dx, dy) = self.step();
+        let u = (position.x - self.min.x) / dx;
+        let v = (position.y - self.min.y) / dy;
+        if !(0.0..=(self.columns - 1) as f32).contains(&u)
+            || !(0.0..=(self.rows - 1) as f32).contains(&v)
+        {
+            return BACKGROUND;
+        }
+        let i = (u as usize).min(self.columns - 2);
+        let j = (v as usize).min(self.rows - 2);
+        let (s, t) = ((u - i as f32) as f64, (v - j as f32) as f64);
+        let value = |i: usize, j: usize| self.values[j * self.columns + i];
+        let interpolated = (1.0 - s) * (1.0 - t) * value(i, j)
+            + s * (1.0 - t) * value(i + 1, j)
+            + (1.0 - s) * t * value(i, j + 1)
+            + s * t * value(i + 1, j + 1);
+        interpolated + BACKGROUND
+    }
+
+    pub fn log_density(&self, position: Point) -> f64 {
+        self.density(position).ln()
+    }
+
+    /// Normalised marginal density of x, or of y when `x_axis` is false.
+    pub fn marginal(&self, value: f32, x_axis: bool) -> f64 {
+        let (dx, dy) = self.step();
+        let total: f64 = self.values.iter().sum::<f64>() * dx as f64 * dy as f64;
+        if total <= 0.0 {
+            return 0.0;
+        }
+        let (nodes, across, step, origin) = if x_axis {
+            (self.columns, self.rows, dx, self.min.x)
+        } else {
+            (self.rows, self.columns, dy, self.min.y)
+        };
+        // integral over the other axis at node k.
+        let line = |k: usize| {
+            let sum: f64 = (0..across)
+                .map(|l| {
+                    if x_axis {
+                        self.values[l * self.columns + k]
+                    } else {
+                        self.values[k * self.columns + l]
+                    }
+                })
+                .sum();
+            sum * if x_axis { dy } else { dx } as f64
+        };
+        let u = (value - origin) / step;
+        if !(0.0..=(nodes - 1) as f32).contains(&u) {
+            return 0.0;
+        }
+        let k = (u as usize).min(nodes - 2);
+        let s = (u - k as f32) as f64;
+        ((1.0 - s) * line(k) + s * line(k + 1)) / total
+    }
+}
@@ -0,0 +1,180 @@
+/**
+ * A small line plot canvas for diagnostics (autocorrelation, distances over
+ * iterations, ...). Each series is drawn scaled to the common data range.
+ */
+use iced::widget::canvas::{self, stroke, Cache, Stroke};
+use iced::{Color, Point, Theme};
+
+pub struct Series {
+    pub label: String,
+    pub color: Color,
+    pub points: Vec<(f64, f64)>,
+}
+
+pub struct LinePlot {
+    pub title: String,
+    pub series: Vec<Series>,
+    // fixed y range, otherwise the range of the data.
+    pub y_range: Option<(f64, f64)>,
+    pub log_y: bool,
+    cache: Cache,
+}
+
+impl LinePlot {
+    pub fn new(title: &str) -> Self {
+        Self {
+            title: String::from(title),
+            series: Vec::new(),
+            y_range: None,
+            log_y: false,
+            cache: canvas::Cache::default(),
+        }
+    }
+
+    pub fn set_series(&mut self, series: Vec<Series>) {
+        self.series = series;
+        self.cache.clear();
+    }
+
+    fn transform_y(&self, y: f64) -> Option<f64> {
+        if !self.log_y {
+            return Some(y);
+        }
+        (y > 0.0).then(|| y.log10())
+    }
+
+    fn bounds(&self) -> Option<((f64, f64), (f64, f64))> {
+        let points = self.series.iter().flat_map(|series| series.points.iter());
+        let mut x_range = (f64::INFINITY, f64::NEG_INFINITY);
+        let mut y_range = (f64::INFINITY, f64::NEG_INFINITY);
+        for (x, y) in points {
+            x_range = (x_range.0.min(*x), x_range.1.max(*x));
+            if let Some(y) = self.transform_y(*y) {
+                y_range = (y_range.0.min(y), y_range.1.max(y));
+            }
+        }
+        if let Some((low, high)) = self.y_range {
+            y_range = (self.transform_y(low)?, self.transform_y(high)?);
+        }
+        if !x_range.0.is_finite() || !y_range.0.is_finite() {
+            return None;
+        }
+        if x_range.0 == x_range.1 {
+            x_range.1 += 1.0;
+        }
+        if y_range.0 == y_range.1 {
+            y_range.1 += 1.0;
+        }
+        Some((x_range, y_range))
+    }
+}
+
+impl<Message> canvas::Program<Message> for LinePlot {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        _theme: &Theme,
+        bounds: iced::Rectangle,
+        _cursor: canvas::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let geom = self.cache.draw(bounds.size(), |frame| {
+            let margin = 24.0;
+            let width = bounds.width - 2.0 * margin;
+            let height = bounds.height - 2.0 * margin;
+            frame.stroke(
+                &canvas::Path::rectangle(
+                    Point::new(margin, margin),
+                    iced::Size::new(width, height),
+                ),
+                canvas::Stroke {
+                    width: 1.0,
+                    style: stroke::Style::Solid(Color::from_rgb8(0x55, 0x55, 0x55)),
+                    ..Stroke::default()
+                },
+            );
+            frame.fill_text(canvas::Text {
+                content: self.title.clone(),
+                position: Point::new(margin, 4.0),
+                color: Color::WHITE,
+                size: 14.0,
+                ..canvas::Text::default()
+            });
+
+            let ((x_min, x_max), (y_min, y_max)) = match self.bounds() {
+                Some(range) => range,
+                None => return,
+            };
+            let to_screen = |x: f64, y: f64| Point {
+                x: margin + ((x - x_min) / (x_max - x_min)) as f32 * width,
+                y: margin + (1.0 - ((y - y_min) / (y_max - y_min)) as f32) * height,
+            };
+            let label = |y: f64| {
+                if self.log_y {
+                    format!("{:.2e}", 10f64.powf(y))
+                } else {
+                    format!("{:.2}", y)
+                }
+            };
+            frame.fill_text(canvas::Text {
+                content: label(y_max),
+                position: Point::new(margin + 2.0, margin + 2.0),
+                color: Color::from_rgb8(0xaa, 0xaa, 0xaa),
+                size: 12.0,
+                ..canvas::Text::default()
+            });
+            frame.fill_text(canvas::Text {
+                content: label(y_min),
+                position: Point::new(margin + 2.0, margin + height - 14.0),
+                color: Color::from_rgb8(0xaa, 0xaa, 0xaa),
+                size: 12.0,
+                ..canvas::Text::default()
+            });
+            if !self.log_y && y_min < 0.0 && y_max > 0.0 {
+                frame.stroke(
+                    &canvas::Path::line(to_screen(x_min, 0.0), to_screen(x_max, 0.0)),
+                    canvas::Stroke {
+                        width: 1.0,
+                        style: stroke::Style::Solid(Color::from_rgb8(0x55, 0x55, 0x55)),
+                        ..Stroke::default()
+                    },
+                );
+            }
+
+            for (i, series) in self.series.iter().enumerate() {
+                frame.stroke(
+                    &canvas::Path::new(|path| {
+                        let mut started = false;
+                        for (x, y) in series.points.iter() {
+                            let y = match self.transform_y(*y) {
+                                Some(y) => y.clamp(y_min, y_max),
+                                None => continue,
+                            };
+                            let point = to_screen(*x, y);
+                            if started {
+                                path.line_to(point);
+                            } else {
+                                path.move_to(point);
+                                started = true;
+                            }
+                        }
+                    }),
+                    canvas::Stroke {
+                        width: 2.0,
+                        style: stroke::Style::Solid(series.color),
+                        ..Stroke::default()
+                    },
+                );
+                frame.fill_text(canvas::Text {
+                    content: series.label.clone(),
+                    position: Point::new(margin + width - 80.0, margin + 2.0 + 14.0 * i as f32),
+                    color: series.color,
+                    size: 12.0,
+                    ..canvas::Text::default()
+                });
+            }
+        });
+        vec![geom]
+    }
+}
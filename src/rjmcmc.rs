@@ -0,0 +1,254 @@
+/**
+ * Reversible-jump MCMC choosing between a one and a two component Gaussian
+ * mixture (known common noise) for a 1D dataset. Trans-dimensional moves are
+ * birth (split one mean into two) and death (merge two means into one); the
+ * within-model moves are random walk Metropolis on the parameters.
+ *
+ * Every chain is drawn on the stage at (mean_1, mean_2), so one component
+ * models sit on the diagonal at (mean, mean).
+ */
+use crate::gaussian;
+
+use iced::Point;
+use rand::Rng;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Model {
+    One { mean: f64 },
+    Two { weight: f64, means: (f64, f64) },
+}
+
+impl Model {
+    pub fn components(&self) -> usize {
+        match self {
+            Model::One { .. } => 1,
+            Model::Two { .. } => 2,
+        }
+    }
+
+    /// Mixture density at `y` with common component std. dev. `noise`.
+    pub fn density(&self, noise: f64, y: f64) -> f64 {
+        match *self {
+            Model::One { mean } => gaussian::distribution_density(mean, noise, y),
+            Model::Two { weight, means } => {
+                weight * gaussian::distribution_density(means.0, noise, y)
+                    + (1.0 - weight) * gaussian::distribution_density(means.1, noise, y)
+            }
+        }
+    }
+
+    pub fn position(&self) -> Point {
+        match *self {
+            Model::One { mean } => Point {
+                x: mean as f32,
+                y: mean as f32,
+            },
+            Model::Two { means, .. } => Point {
+                x: means.0 as f32,
+                y: means.1 as f32,
+            },
+        }
+    }
+}
+
+pub struct MixtureData {
+    pub y: Vec<f64>,
+    pub noise: f64,
+    pub prior_mean: f64,
+    pub prior_dev: f64,
+}
+
+impl MixtureData {
+    pub fn synthetic(n: usize, weight: f64, means: (f64, f64), noise: f64) -> Self {
+        let mut rng = rand::thread_rng();
+        let y: Vec<f64> = (0..n)
+            .map(|_| {
+                let mean = if rng.gen::<f64>() < weight {
+                    means.0
+                } else {
+                    means.1
+                };
+                gaussian::sample_custom(mean, noise)
+            })
+            .collect();
+        // centred on the data, or on the mixture's mean when there is none.
+        let prior_mean = if y.is_empty() {
+            weight * means.0 + (1.0 - weight) * means.1
+        } else {
+            y.iter().sum::<f64>() / n as f64
+        };
+        MixtureData {
+            y,
+            noise,
+            prior_mean,
+            prior_dev: 2.0,
+        }
+    }
+
+    pub fn log_likelihood(&self, model: &Model) -> f64 {
+        self.y
+            .iter()
+            .map(|y| model.density(self.noise, *y).ln())
+            .sum()
+    }
+
+    /// Log prior of the parameters; the weight is uniform and both models are
+    /// a priori equally likely, so only the means contribute.
+    pub fn log_prior(&self, model: &Model) -> f64 {
+        let prior = |mean: f64| gaussian::log_density(self.prior_mean, self.prior_dev, mean);
+        match *model {
+            Model::One { mean } => prior(mean),
+            Model::Two { weight, means } => {
+                if !(0.0..=1.0).contains(&weight) {
+                    return f64::NEG_INFINITY;
+                }
+                prior(means.0) + prior(means.1)
+            }
+        }
+    }
+
+    pub fn log_posterior(&self, model: &Model) -> f64 {
+        let prior = self.log_prior(model);
+        if prior == f64::NEG_INFINITY {
+            return prior;
+        }
+        prior + self.log_likelihood(model)
+    }
+}
+
+/// Running averages of the within-model parameters, with the two means sorted
+/// to undo label switching.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Summary {
+    pub visits: [usize; 2],
+    pub one_mean: f64,
+    pub two_weight: f64,
+    pub two_means: (f64, f64),
+}
+
+impl Summary {
+    pub fn probability(&self, components: usize) -> f64 {
+        let total = self.visits[0] + self.visits[1];
+        if total == 0 {
+            return 0.0;
+        }
+        self.visits[components - 1] as f64 / total as f64
+    }
+
+    fn record(&mut self, model: &Model) {
+        match *model {
+            Model::One { mean } => {
+                self.visits[0] += 1;
+                let n = self.visits[0] as f64;
+                self.one_mean += (mean - self.one_mean) / n;
+            }
+            Model::Two { weight, means } => {
+                self.visits[1] += 1;
+                let n = self.visits[1] as f64;
+                let (weight, means) = if means.0 <= means.1 {
+                    (weight, means)
+                } else {
+                    (1.0 - weight, (means.1, means.0))
+                };
+                self.two_weight += (weight - self.two_weight) / n;
+                self.two_means.0 += (means.0 - self.two_means.0) / n;
+                self.two_means.1 += (means.1 - self.two_means.1) / n;
+            }
+        }
+    }
+}
+
+pub struct ReversibleJump {
+    pub data: MixtureData,
+    pub chains: Vec<Model>,
+    pub summary: Summary,
+    // std. dev. of the split variable in birth moves.
+    pub jump_scale: f64,
+    pub step_size: f64,
+    pub iteration: usize,
+    pub jumps_proposed: usize,
+    pub jumps_accepted: usize,
+}
+
+impl ReversibleJump {
+    pub fn new(data: MixtureData, chains: Vec<Model>) -> Self {
+        ReversibleJump {
+            data,
+            chains,
+            summary: Summary::default(),
+            jump_scale: 0.5,
+            step_size: 0.1,
+            iteration: 0,
+            jumps_proposed: 0,
+            jumps_accepted: 0,
+        }
+    }
+
+    /// Updates every chain once and returns whether each accepted its move.
+    pub fn step(&mut self) -> Vec<bool> {
+        let mut rng = rand::thread_rng();
+        self.iteration += 1;
+        let mut moves = Vec::with_capacity(self.chains.len());
+        for i in 0..self.chains.len() {
+            let model = self.chains[i];
+            let (candidate, log_correction, jump) = if rng.gen::<f64>() < 0.5 {
+                let (candidate, log_correction) = self.propose_jump(&model);
+                (candidate, log_correction, true)
+            } else {
+                (self.propose_within(&model), 0.0, false)
+            };
+            let log_ratio = self.data.log_posterior(&candidate) - self.data.log_posterior(&model)
+                + log_correction;
+            let accepted = log_ratio >= 0.0 || rng.gen::<f64>().ln() < log_ratio;
+            if jump {
+                self.jumps_proposed += 1;
+                if accepted {
+                    self.jumps_accepted += 1;
+                }
+            }
+            if accepted {
+                self.chains[i] = candidate;
+            }
+            self.summary.record(&self.chains[i]);
+            moves.push(accepted);
+        }
+        moves
+    }
+
+    /// Birth from one component or death from two, returning the candidate and
+    /// the log of proposal density ratio times the Jacobian.
+    fn propose_jump(&self, model: &Model) -> (Model, f64) {
+        let mut rng = rand::thread_rng();
+        match *model {
+            Model::One { mean } => {
+                let u = gaussian::sample_custom(0.0, self.jump_scale);
+                let weight = rng.gen::<f64>();
+                let candidate = Model::Two {
+                    weight,
+                    means: (mean - u, mean + u),
+                };
+                let log_q = gaussian::log_density(0.0, self.jump_scale, u);
+                (candidate, 2f64.ln() - log_q)
+            }
+            Model::Two { means, .. } => {
+                let u = (means.1 - means.0) / 2.0;
+                let candidate = Model::One {
+                    mean: (means.0 + means.1) / 2.0,
+                };
+                let log_q = gaussian::log_density(0.0, self.jump_scale, u);
+                (candidate, log_q - 2f64.ln())
+            }
+        }
+    }
+
+    fn propose_within(&self, model: &Model) -> Model {
+        let step = |x: f64| gaussian::sample_custom(x, self.step_size);
+        match *model {
+            Model::One { mean } => Model::One { mean: step(mean) },
+            Model::Two { weight, means } => Model::Two {
+                weight: step(weight),
+                means: (step(means.0), step(means.1)),
+            },
+        }
+    }
+}
@@ -0,0 +1,195 @@
+/**
+ * Stochastic-gradient Langevin dynamics (SGLD) and stochastic-gradient
+ * Hamiltonian Monte Carlo (SGHMC) on the posterior of a Bayesian linear
+ * regression y = w0 + w1 * x + noise. The two weights are the coordinates the
+ * stage plots. Neither sampler applies a Metropolis correction, so with a
+ * finite step size the cloud is biased away from the exact posterior, which is
+ * available in closed form for comparison.
+ */
+use crate::gaussian;
+
+use iced::Point;
+use rand::seq::index;
+use rand::Rng;
+
+pub struct Dataset {
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+    pub noise: f64,
+    pub prior_dev: f64,
+}
+
+/// Gaussian posterior over (w0, w1).
+pub struct Posterior {
+    pub mean: (f64, f64),
+    pub cov: [[f64; 2]; 2],
+}
+
+impl Dataset {
+    pub fn synthetic(n: usize, weights: (f64, f64), noise: f64) -> Self {
+        let mut rng = rand::thread_rng();
+        let x: Vec<f64> = (0..n).map(|_| rng.gen_range(0.0..2.0)).collect();
+        let y = x
+            .iter()
+            .map(|x| gaussian::sample_custom(weights.0 + weights.1 * x, noise))
+            .collect();
+        Dataset {
+            x,
+            y,
+            noise,
+            prior_dev: 10.0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.x.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.x.is_empty()
+    }
+
+    pub fn posterior(&self) -> Posterior {
+        let noise_prec = 1.0 / (self.noise * self.noise);
+        let prior_prec = 1.0 / (self.prior_dev * self.prior_dev);
+        let n = self.len() as f64;
+        let sx: f64 = self.x.iter().sum();
+        let sxx: f64 = self.x.iter().map(|x| x * x).sum();
+        let sy: f64 = self.y.iter().sum();
+        let sxy: f64 = self.x.iter().zip(&self.y).map(|(x, y)| x * y).sum();
+
+        let a = prior_prec + noise_prec * n;
+        let b = noise_prec * sx;
+        let d = prior_prec + noise_prec * sxx;
+        let det = a * d - b * b;
+        let cov = [[d / det, -b / det], [-b / det, a / det]];
+        let rhs = (noise_prec * sy, noise_prec * sxy);
+        Posterior {
+            mean: (
+                cov[0][0] * rhs.0 + cov[0][1] * rhs.1,
+                cov[1][0] * rhs.0 + cov[1][1] * rhs.1,
+            ),
+            cov,
+        }
+    }
+
+    /// Unbiased minibatch estimate of the gradient of the log posterior. With
+    /// no data that is the prior's gradient.
+    pub fn minibatch_gradient(&self, theta: Point, batch_size: usize) -> (f64, f64) {
+        let (w0, w1) = (theta.x as f64, theta.y as f64);
+        let prior_prec = 1.0 / (self.prior_dev * self.prior_dev);
+        if self.is_empty() {
+            return (-w0 * prior_prec, -w1 * prior_prec);
+        }
+        let noise_prec = 1.0 / (self.noise * self.noise);
+        let batch_size = batch_size.clamp(1, self.len());
+        let scale = self.len() as f64 / batch_size as f64;
+
+        let mut grad = (-w0 * prior_prec, -w1 * prior_prec);
+        for i in index::sample(&mut rand::thread_rng(), self.len(), batch_size) {
+            let residual = self.y[i] - (w0 + w1 * self.x[i]);
+            grad.0 += scale * noise_prec * residual;
+            grad.1 += scale * noise_prec * residual * self.x[i];
+        }
+        grad
+    }
+}
+
+impl Posterior {
+    pub fn log_density(&self, theta: Point) -> f64 {
+        let det = self.cov[0][0] * self.cov[1][1] - self.cov[0][1] * self.cov[1][0];
+        let dx = theta.x as f64 - self.mean.0;
+        let dy = theta.y as f64 - self.mean.1;
+        // quadratic form with the inverse covariance.
+        let q = (self.cov[1][1] * dx * dx - 2.0 * self.cov[0][1] * dx * dy
+            + self.cov[0][0] * dy * dy)
+            / det;
+        -0.5 * q - (2.0 * std::f64::consts::PI).ln() - 0.5 * det.ln()
+    }
+
+    /// Contour of the posterior at `k` standard deviations (Mahalanobis).
+    pub fn ellipse(&self, k: f64, segments: usize) -> Vec<Point> {
+        let l11 = self.cov[0][0].sqrt();
+        let l21 = self.cov[1][0] / l11;
+        let l22 = (self.cov[1][1] - l21 * l21).sqrt();
+        (0..=segments)
+            .map(|i| {
+                let t = 2.0 * std::f64::consts::PI * i as f64 / segments as f64;
+                let (u, v) = (k * t.cos(), k * t.sin());
+                Point {
+                    x: (self.mean.0 + l11 * u) as f32,
+                    y: (self.mean.1 + l21 * u + l22 * v) as f32,
+                }
+            })
+            .collect()
+    }
+}
+
+pub struct Sgld {
+    pub step_size: f64,
+    // polynomial decay exponent, step = step_size * (1 + t)^-decay.
+    pub decay: f64,
+    pub batch_size: usize,
+    // SGHMC friction term.
+    pub friction: f64,
+    pub iteration: usize,
+    momenta: Vec<(f64, f64)>,
+}
+
+impl Sgld {
+    pub fn new(step_size: f64, decay: f64, batch_size: usize) -> Self {
+        Sgld {
+            step_size,
+            decay,
+            batch_size,
+            friction: 1.0,
+            iteration: 0,
+            momenta: Vec::new(),
+        }
+    }
+
+    pub fn current_step_size(&self) -> f64 {
+        self.step_size * (1.0 + self.iteration as f64).powf(-self.decay)
+    }
+
+    pub fn advance(&mut self) {
+        self.iteration += 1;
+    }
+
+    pub fn reset(&mut self) {
+        self.iteration = 0;
+        self.momenta.clear();
+    }
+
+    /// theta += eps / 2 * grad + N(0, eps)
+    pub fn sgld_step(&self, data: &Dataset, theta: Point) -> Point {
+        let eps = self.current_step_size();
+        let grad = data.minibatch_gradient(theta, self.batch_size);
+        Point {
+            x: (theta.x as f64 + 0.5 * eps * grad.0 + gaussian::sample_custom(0.0, eps.sqrt()))
+                as f32,
+            y: (theta.y as f64 + 0.5 * eps * grad.1 + gaussian::sample_custom(0.0, eps.sqrt()))
+                as f32,
+        }
+    }
+
+    /// One SGHMC step for the chain with index `chain`, which owns a momentum.
+    pub fn sghmc_step(&mut self, data: &Dataset, chain: usize, theta: Point) -> Point {
+        if self.momenta.len() <= chain {
+            self.momenta.resize(chain + 1, (0.0, 0.0));
+        }
+        let eps = self.current_step_size();
+        let noise = (2.0 * self.friction * eps).sqrt();
+        let v = self.momenta[chain];
+        let next = Point {
+            x: (theta.x as f64 + eps * v.0) as f32,
+            y: (theta.y as f64 + eps * v.1) as f32,
+        };
+        let grad = data.minibatch_gradient(next, self.batch_size);
+        self.momenta[chain] = (
+            v.0 + eps * grad.0 - eps * self.friction * v.0 + gaussian::sample_custom(0.0, noise),
+            v.1 + eps * grad.1 - eps * self.friction * v.1 + gaussian::sample_custom(0.0, noise),
+        );
+        next
+    }
+}
@@ -0,0 +1,195 @@
+/**
+ * Sequential Monte Carlo with likelihood tempering. The particle cloud starts
+ * from the prior and moves towards the target through the bridging densities
+ *   pi_beta(x) ∝ prior(x)^(1 - beta) * target(x)^beta
+ * with beta going from 0 to 1. Each step reweights, resamples when the
+ * effective sample size drops below a threshold, and rejuvenates every
+ * particle with a random walk Metropolis move targeting pi_beta.
+ */
+use crate::gaussian;
+
+use iced::Point;
+use rand::Rng;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resampling {
+    Systematic,
+    Multinomial,
+}
+
+impl Resampling {
+    pub const ALL: [Resampling; 2] = [Resampling::Systematic, Resampling::Multinomial];
+}
+
+impl fmt::Display for Resampling {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Resampling::Systematic => "Systematic",
+                Resampling::Multinomial => "Multinomial",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ResampleEvent {
+    pub iteration: usize,
+    pub beta: f64,
+    pub ess: f64,
+}
+
+pub struct Smc {
+    pub beta: f64,
+    // increment of beta per step.
+    pub delta: f64,
+    // resample when ess / particles falls below this fraction.
+    pub ess_threshold: f64,
+    pub step_size: f64,
+    pub resampling: Resampling,
+    pub log_weights: Vec<f64>,
+    pub iteration: usize,
+    pub events: Vec<ResampleEvent>,
+}
+
+impl Smc {
+    pub fn new(particles: usize, delta: f64) -> Self {
+        Smc {
+            beta: 0.0,
+            delta,
+            ess_threshold: 0.5,
+            step_size: 0.2,
+            resampling: Resampling::Systematic,
+            log_weights: vec![0.0; particles],
+            iteration: 0,
+            events: Vec::new(),
+        }
+    }
+
+    pub fn weights(&self) -> Vec<f64> {
+        normalised_weights(&self.log_weights)
+    }
+
+    pub fn ess(&self) -> f64 {
+        ess(&self.weights())
+    }
+
+    /// Advances the cloud one tempering step and returns whether each
+    /// particle's rejuvenation move was accepted.
+    pub fn step<P, T>(&mut self, positions: &mut [Point], log_prior: P, log_target: T) -> Vec<bool>
+    where
+        P: Fn(Point) -> f64,
+        T: Fn(Point) -> f64,
+    {
+        debug_assert_eq!(self.log_weights.len(), positions.len());
+        self.iteration += 1;
+        let beta = f64::min(self.beta + self.delta, 1.0);
+        for (log_weight, position) in self.log_weights.iter_mut().zip(positions.iter()) {
+            *log_weight += (beta - self.beta) * (log_target(*position) - log_prior(*position));
+        }
+        self.beta = beta;
+
+        let ess = self.ess();
+        if ess < self.ess_threshold * positions.len() as f64 {
+            let weights = self.weights();
+            let indices = match self.resampling {
+                Resampling::Systematic => systematic_resample(&weights, positions.len()),
+                Resampling::Multinomial => multinomial_resample(&weights, positions.len()),
+            };
+            let old = positions.to_vec();
+            for (position, index) in positions.iter_mut().zip(indices) {
+                *position = old[index];
+            }
+            self.log_weights.iter_mut().for_each(|w| *w = 0.0);
+            self.events.push(ResampleEvent {
+                iteration: self.iteration,
+                beta,
+                ess,
+            });
+        }
+
+        let log_tempered = |p: Point| (1.0 - beta) * log_prior(p) + beta * log_target(p);
+        positions
+            .iter_mut()
+            .map(|position| {
+                let (next, accepted) = rejuvenate(log_tempered, *position, self.step_size);
+                *position = next;
+                accepted
+            })
+            .collect()
+    }
+}
+
+fn rejuvenate<F>(log_density: F, position: Point, step_size: f64) -> (Point, bool)
+where
+    F: Fn(Point) -> f64,
+{
+    let candidate = Point {
+        x: gaussian::sample_custom(position.x as f64, step_size) as f32,
+        y: gaussian::sample_custom(position.y as f64, step_size) as f32,
+    };
+    let log_ratio = log_density(candidate) - log_density(position);
+    if log_ratio >= 0.0 || rand::thread_rng().gen::<f64>().ln() < log_ratio {
+        (candidate, true)
+    } else {
+        (position, false)
+    }
+}
+
+/// Normalises log weights into weights that sum to one.
+pub fn normalised_weights(log_weights: &[f64]) -> Vec<f64> {
+    let max = log_weights
+        .iter()
+        .cloned()
+        .fold(f64::NEG_INFINITY, f64::max);
+    let weights: Vec<f64> = log_weights.iter().map(|w| (w - max).exp()).collect();
+    let total: f64 = weights.iter().sum();
+    weights.iter().map(|w| w / total).collect()
+}
+
+/// Effective sample size of normalised weights, 1 / sum(w^2).
+pub fn ess(weights: &[f64]) -> f64 {
+    1.0 / weights.iter().map(|w| w * w).sum::<f64>()
+}
+
+pub fn systematic_resample(weights: &[f64], n: usize) -> Vec<usize> {
+    if weights.is_empty() {
+        return Vec::new();
+    }
+    let offset: f64 = rand::thread_rng().gen();
+    let mut indices = Vec::with_capacity(n);
+    let mut cumulative = weights[0];
+    let mut j = 0;
+    for i in 0..n {
+        let u = (i as f64 + offset) / n as f64;
+        while u >= cumulative && j < weights.len() - 1 {
+            j += 1;
+            cumulative += weights[j];
+        }
+        indices.push(j);
+    }
+    indices
+}
+
+pub fn multinomial_resample(weights: &[f64], n: usize) -> Vec<usize> {
+    if weights.is_empty() {
+        return Vec::new();
+    }
+    let mut rng = rand::thread_rng();
+    (0..n)
+        .map(|_| {
+            let u: f64 = rng.gen();
+            let mut cumulative = 0.0;
+            weights
+                .iter()
+                .position(|w| {
+                    cumulative += w;
+                    u < cumulative
+                })
+                .unwrap_or(weights.len() - 1)
+        })
+        .collect()
+}
@@ -1,191 +1,1460 @@
 /**
  * The 'stage' area where the metropis hastings alg will be plotted.
  */
+use crate::acceptance::AcceptanceRate;
+use crate::colormap;
+use crate::contour::{self, Grid};
 use crate::gaussian;
-use crate::metropolis::Candidate;
+use crate::histogram::{Histogram, Histogram2d};
+use crate::history::{self, History};
+use crate::init;
+use crate::metropolis::{Candidate, Explanation};
+use crate::painted::GridDensity;
+use crate::viewport::{self, Viewport};
 
-use iced::widget::canvas::{self, stroke, Cache, Stroke};
-use iced::{Color, Point, Theme};
+use iced::widget::canvas::{self, event, stroke, Cache, Stroke};
+use iced::{alignment, mouse, Color, Point, Size, Theme, Vector};
+use std::collections::VecDeque;
+use std::fmt;
 
+// how many players can be selected for the trace plots at once.
+pub const MAX_SELECTED: usize = 5;
+pub const SELECTION_COLORS: [Color; MAX_SELECTED] = [
+    Color::from_rgb(0.96, 0.64, 0.38),
+    Color::from_rgb(0.16, 0.62, 0.56),
+    Color::from_rgb(0.91, 0.77, 0.42),
+    Color::from_rgb(0.62, 0.47, 0.85),
+    Color::from_rgb(0.94, 0.94, 0.94),
+];
+// resolution of the marginal histograms, which cover the sampled density.
+const HISTOGRAM_BINS: usize = 80;
+// std. devs. either side of a Gaussian's mean that count as its domain.
+const DOMAIN_REACH: f32 = 5.0;
+const HEATMAP_BINS: usize = 60;
+// how close (in pixels) a click has to be to select a player.
+const SELECT_RADIUS: f32 = 10.0;
+// height of the marginal curves, in model units per unit of density.
+const MARGINAL_SCALE: f32 = 0.4;
+const CURVE_POINTS: usize = 200;
+// room (in pixels) left of and below the plot for tick labels and axis titles.
+const AXIS_MARGIN: (f32, f32) = (56.0, 40.0);
+const PLOT_PADDING: f32 = 10.0;
+const TICK_LENGTH: f32 = 5.0;
+// approximate distance in pixels between ticks.
+const TICK_SPACING: f32 = 80.0;
+// probability mass inside the highest density contours of the target.
+const HPD_LEVELS: [f64; 3] = [0.5, 0.9, 0.99];
+// cells per axis of the target density background, and nodes per axis of the
+// grid the HPD levels are computed on.
+const DENSITY_CELLS: usize = 100;
+const LEVEL_NODES: usize = 200;
+pub const DEFAULT_TRAIL_LENGTH: usize = 20;
+// longest line (in pixels) drawn towards a rejected proposal.
+const REJECTED_TICK: f32 = 8.0;
+// widths and line height of the text boxes on the stage, in pixels.
+const EXPLANATION_WIDTH: f32 = 330.0;
+const INSPECTOR_WIDTH: f32 = 230.0;
+const TEXT_LINE: f32 = 16.0;
+// extent and resolution of the paintable grid, in model coordinates.
+const PAINT_MIN: Point = Point { x: -4.0, y: -4.0 };
+const PAINT_MAX: Point = Point { x: 8.0, y: 8.0 };
+const PAINT_NODES: usize = 161;
+pub const DEFAULT_BRUSH_RADIUS: f32 = 0.5;
+// density added at the centre of the brush per paint event.
+const BRUSH_AMOUNT: f64 = 0.2;
+// drags shorter than this (in pixels) place a single player.
+const PLACE_DRAG: f32 = 4.0;
+// zoom factor per line scrolled; pixel scrolling counts PIXELS_PER_LINE as one line.
+const ZOOM_STEP: f32 = 1.2;
+const PIXELS_PER_LINE: f32 = 50.0;
+
+/// What the density heatmap under the players shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeatmapMode {
+    Off,
+    Current,
+    AllSamples,
+}
+
+impl HeatmapMode {
+    pub const ALL: [HeatmapMode; 3] = [
+        HeatmapMode::Off,
+        HeatmapMode::Current,
+        HeatmapMode::AllSamples,
+    ];
+}
+
+impl fmt::Display for HeatmapMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                HeatmapMode::Off => "No heatmap",
+                HeatmapMode::Current => "Current positions",
+                HeatmapMode::AllSamples => "All samples so far",
+            }
+        )
+    }
+}
+
+/// What clicking and dragging on the stage does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    // click a player to select it, drag elsewhere to pan.
+    Select,
+    // click to start a chain there, drag a rectangle to start a batch in it.
+    Place,
+    // brush density onto the painted target.
+    Paint,
+}
+
+impl Tool {
+    pub const ALL: [Tool; 3] = [Tool::Select, Tool::Place, Tool::Paint];
+}
+
+impl fmt::Display for Tool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Tool::Select => "Select / pan",
+                Tool::Place => "Place players",
+                Tool::Paint => "Paint target",
+            }
+        )
+    }
+}
+
+/// Density the chains sample and the stage draws.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    // independent normal with the stage's mean and stddev.
+    Gaussian,
+    Painted,
+}
+
+impl Target {
+    pub const ALL: [Target; 2] = [Target::Gaussian, Target::Painted];
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Target::Gaussian => "Gaussian target",
+                Target::Painted => "Painted target",
+            }
+        )
+    }
+}
+
+/// Interactions on the stage reported back to the application. Positions
+/// are in model coordinates.
+#[derive(Debug, Clone, Copy)]
+pub enum StageEvent {
+    PlayerClicked(usize),
+    ViewportChanged(Viewport),
+    PlayerPlaced(Point),
+    // opposite corners of a region to start a batch of players in.
+    RegionPlaced(Point, Point),
+    Painted(Point),
+}
+
+/// Mouse interaction in progress on the stage, kept as the canvas state.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum Interaction {
+    #[default]
+    None,
+    // dragging the view: cursor position and viewport when the drag started.
+    Panning {
+        start: Point,
+        viewport: Viewport,
+    },
+    // dragging out a region to place players in, relative to the plot area.
+    Placing {
+        start: Point,
+        end: Point,
+    },
+    Painting,
+}
+
+/// One step of a chain, as drawn in its trail.
+#[derive(Debug, Clone, Copy)]
+pub struct Step {
+    pub from: Point,
+    pub to: Point,
+    // candidate the step proposed, for samplers with an accept/reject step.
+    pub proposal: Option<Point>,
+}
+
+impl Step {
+    pub fn accepted(&self) -> bool {
+        self.from != self.to
+    }
+}
+
+/// A single chain. All positions are in model coordinates.
 pub struct Player {
     pub candidate: Candidate,
     pub current: Point,
+    // log of the sampled density at `current`, `None` until it is evaluated.
+    pub log_density: Option<f64>,
+    // line searched by the last hit-and-run step, if any.
+    pub line: Option<(Point, Point)>,
+    // importance weight relative to the mean weight, drawn as the dot's area.
+    pub weight: f32,
+    pub history: History,
+    pub acceptance: AcceptanceRate,
+    // most recent steps, oldest first.
+    pub trail: VecDeque<Step>,
 }
-impl Default for Player {
-    fn default() -> Self {
-        Player {
-            candidate: Candidate::new((0.0, 0.0), Point { x: 0.0, y: 5.0 }),
-            current: Point {
-                x: gaussian::sample_custom(2.0, 0.2) as f32 * 250.0,
-                y: gaussian::sample_custom(2.0, 0.2) as f32 * 250.0,
-            },
+impl Player {
+    /// Records the step from `from` to the current position, keeping at most
+    /// `length` steps.
+    pub fn record_step(&mut self, from: Point, proposal: Option<Point>, length: usize) {
+        self.trail.push_back(Step {
+            from,
+            to: self.current,
+            proposal,
+        });
+        while self.trail.len() > length {
+            self.trail.pop_front();
         }
     }
 }
-impl Clone for Player {
-    fn clone(&self) -> Self {
-        Player::default()
+impl Player {
+    /// A fresh player starting at `position`, with no candidate proposed yet.
+    pub fn at(position: Point) -> Self {
+        Player {
+            candidate: Candidate::new((0.0, 0.0), position),
+            current: position,
+            log_density: None,
+            line: None,
+            weight: 1.0,
+            history: History::default(),
+            acceptance: AcceptanceRate::default(),
+            trail: VecDeque::new(),
+        }
     }
 }
 pub struct Stage {
     pub players: Vec<Player>,
     pub mean: Point,
     pub stddev: Point,
+    // best point found so far when annealing.
+    pub best: Option<Point>,
+    // reference curves (e.g. exact posterior contours) drawn under the players.
+    pub overlay: Vec<Vec<Point>>,
+    // indices of players highlighted and shown in the trace plots.
+    pub selected: Vec<usize>,
+    // every draw the chains produced, per axis.
+    pub x_histogram: Histogram,
+    pub y_histogram: Histogram,
+    pub heatmap: Histogram2d,
+    pub heatmap_mode: HeatmapMode,
+    // where the density the sampler explores has its mass, when that is not
+    // the stage's target.
+    sampled_domain: Option<(Point, Point)>,
+    pub viewport: Viewport,
+    // whether the target (mean, stddev) is drawn as a background with contours.
+    pub show_target: bool,
+    // number of recent steps drawn behind each player.
+    pub trail_length: usize,
+    // last Metropolis step of a selected player, annotated in step mode.
+    pub explanation: Option<(usize, Explanation)>,
+    pub tool: Tool,
+    pub target: Target,
+    pub painted: GridDensity,
+    // brush radius in model units, and whether the brush removes density.
+    pub brush_radius: f32,
+    pub erasing: bool,
+    // capacity and thinning of the history every player keeps.
+    history_capacity: usize,
+    history_thinning: usize,
     line_cache: Cache,
     position_cache: Cache,
     x_curve_cache: Cache,
     y_curve_cache: Cache,
+    histogram_cache: Cache,
+    heatmap_cache: Cache,
+    density_cache: Cache,
 }
 
 impl Default for Stage {
     fn default() -> Self {
-        Self {
-            mean: Point { x: 2.0, y: 2.0 },
-            stddev: Point { x: 0.2, y: 0.2 },
-            players: vec![Player::default(); 100],
-            line_cache: canvas::Cache::default(),
-            position_cache: canvas::Cache::default(),
-            x_curve_cache: canvas::Cache::default(),
-            y_curve_cache: canvas::Cache::default(),
-        }
+        let (mean, stddev) = (Point { x: 2.0, y: 2.0 }, Point { x: 0.2, y: 0.2 });
+        let viewport = Viewport::default();
+        let setting = init::Setting {
+            point: viewport.center(),
+            target_mean: mean,
+            target_stddev: stddev,
+            region: (viewport.min, viewport.max),
+            ..Default::default()
+        };
+        Stage::new(mean, stddev, 100, init::Strategy::Overdispersed, &setting)
     }
 }
 
 impl Stage {
-    pub fn new(mean: Point, stddev: Point, player_num: usize) -> Self {
+    /// A stage targeting N(mean, stddev) whose `player_num` players start
+    /// where `strategy` places them.
+    pub fn new(
+        mean: Point,
+        stddev: Point,
+        player_num: usize,
+        strategy: init::Strategy,
+        setting: &init::Setting,
+    ) -> Self {
+        let (x_histogram, y_histogram, heatmap) = histograms(gaussian_domain(mean, stddev));
         Self {
             mean,
             stddev,
-            players: vec![Player::default(); player_num],
+            players: init::positions(strategy, player_num, setting)
+                .into_iter()
+                .map(Player::at)
+                .collect(),
+            best: None,
+            overlay: Vec::new(),
+            selected: Vec::new(),
+            x_histogram,
+            y_histogram,
+            heatmap,
+            heatmap_mode: HeatmapMode::Off,
+            sampled_domain: None,
+            viewport: Viewport::default(),
+            show_target: true,
+            trail_length: DEFAULT_TRAIL_LENGTH,
+            explanation: None,
+            tool: Tool::Select,
+            target: Target::Gaussian,
+            painted: GridDensity::new(PAINT_MIN, PAINT_MAX, PAINT_NODES, PAINT_NODES),
+            brush_radius: DEFAULT_BRUSH_RADIUS,
+            erasing: false,
+            history_capacity: history::DEFAULT_CAPACITY,
+            history_thinning: 1,
             line_cache: canvas::Cache::default(),
             position_cache: canvas::Cache::default(),
             x_curve_cache: canvas::Cache::default(),
             y_curve_cache: canvas::Cache::default(),
+            histogram_cache: canvas::Cache::default(),
+            heatmap_cache: canvas::Cache::default(),
+            density_cache: canvas::Cache::default(),
+        }
+    }
+
+    /// A fresh player starting at `position`, keeping the history configured
+    /// with `set_history`.
+    pub fn new_player(&self, position: Point) -> Player {
+        Player {
+            history: History::new(self.history_capacity, self.history_thinning),
+            ..Player::at(position)
         }
     }
 
+    /// Replaces the players with fresh ones starting at `positions`.
+    pub fn set_players(&mut self, positions: Vec<Point>) {
+        self.players = positions.into_iter().map(|p| self.new_player(p)).collect();
+    }
+
+    pub fn add_player(&mut self, position: Point) {
+        self.players.push(self.new_player(position));
+    }
+
+    /// Adds `count` players spread uniformly over the rectangle with corners
+    /// `a` and `b`.
+    pub fn place_uniform(&mut self, a: Point, b: Point, count: usize) {
+        for position in init::uniform(a, b, count) {
+            self.add_player(position);
+        }
+    }
+
+    /// Applies a history capacity and thinning to every player, now and when
+    /// players are created later.
+    pub fn set_history(&mut self, capacity: usize, thinning: usize) {
+        self.history_capacity = capacity;
+        self.history_thinning = thinning;
+        for player in self.players.iter_mut() {
+            player.history.set_capacity(capacity);
+            player.history.set_thinning(thinning);
+        }
+    }
+
+    pub fn clear_history(&mut self) {
+        for player in self.players.iter_mut() {
+            player.history.clear();
+            player.acceptance.clear();
+            player.trail.clear();
+        }
+        self.x_histogram.clear();
+        self.y_histogram.clear();
+        self.heatmap.clear();
+        self.explanation = None;
+    }
+
+    /// Drops the first `count` draws of the chains as `chains` aligns them,
+    /// along with anything older, and takes them back out of the histograms.
+    /// Draws already evicted from the bounded histories stay counted.
+    pub fn apply_burn_in(&mut self, count: usize) {
+        let len = self.common_len();
+        let discarded: Vec<Point> = self
+            .players
+            .iter_mut()
+            .flat_map(|player| {
+                let older = player.history.len() - len;
+                player.history.discard(older + count)
+            })
+            .map(|sample| sample.position)
+            .collect();
+        for position in discarded {
+            self.x_histogram.remove(position.x as f64);
+            self.y_histogram.remove(position.y as f64);
+            self.heatmap.remove(position.x as f64, position.y as f64);
+        }
+    }
+
+    /// Refills the histograms from the draws the players still store.
+    fn refill_histograms(&mut self) {
+        self.x_histogram.clear();
+        self.y_histogram.clear();
+        self.heatmap.clear();
+        let draws: Vec<Point> = self
+            .players
+            .iter()
+            .flat_map(|player| player.history.samples().map(|s| s.position))
+            .collect();
+        for position in draws {
+            self.record_draw(position);
+        }
+    }
+
+    /// Moves the histograms to the domain of the sampled density if it
+    /// changed. Only the stored draws can be binned again.
+    fn fit_histograms(&mut self) {
+        let (min, max) = self.sampled_domain.unwrap_or_else(|| self.target_domain());
+        let fitted = (self.x_histogram.min, self.x_histogram.max) == (min.x as f64, max.x as f64)
+            && (self.y_histogram.min, self.y_histogram.max) == (min.y as f64, max.y as f64);
+        if fitted {
+            return;
+        }
+        (self.x_histogram, self.y_histogram, self.heatmap) = histograms((min, max));
+        self.refill_histograms();
+    }
+
+    /// Region holding the density the sampler explores when it is not the
+    /// target drawn on the stage, `None` to go back to the target.
+    pub fn set_sampled_domain(&mut self, domain: Option<(Point, Point)>) {
+        self.sampled_domain = domain;
+        self.fit_histograms();
+    }
+
+    /// Adds a draw (in model coordinates) to the marginal histograms.
+    pub fn record_draw(&mut self, position: Point) {
+        self.x_histogram.add(position.x as f64);
+        self.y_histogram.add(position.y as f64);
+        self.heatmap.add(position.x as f64, position.y as f64);
+    }
+
+    /// Selects the player, or deselects it if it already was. The oldest
+    /// selection is dropped once `MAX_SELECTED` players are selected.
+    pub fn toggle_selected(&mut self, index: usize) {
+        if let Some(i) = self.selected.iter().position(|&s| s == index) {
+            self.selected.remove(i);
+            return;
+        }
+        if self.selected.len() == MAX_SELECTED {
+            self.selected.remove(0);
+        }
+        self.selected.push(index);
+    }
+
+    /// Player drawn closest to the screen `position` on a canvas of `size`.
+    fn player_at(&self, position: Point, size: Size) -> Option<usize> {
+        self.players
+            .iter()
+            .enumerate()
+            .map(|(i, player)| {
+                let screen = self.viewport.to_screen(player.current, size);
+                (i, screen.distance(position))
+            })
+            .filter(|(_, distance)| *distance <= SELECT_RADIUS)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    }
+
+    /// Stored x and y draws of every player, truncated to a common length so
+    /// they can be fed to the multi-chain diagnostics.
+    pub fn chains(&self) -> (Vec<Vec<f64>>, Vec<Vec<f64>>) {
+        let len = self.common_len();
+        let truncate = |mut chain: Vec<f64>| chain.split_off(chain.len() - len);
+        self.players
+            .iter()
+            .map(|player| (truncate(player.history.xs()), truncate(player.history.ys())))
+            .unzip()
+    }
+
+    /// Length of the shortest stored history, the one `chains` truncates to.
+    fn common_len(&self) -> usize {
+        self.players
+            .iter()
+            .map(|player| player.history.len())
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// Acceptance rate pooled over all players, overall and over the recent
+    /// window.
+    pub fn acceptance_rate(&self) -> (Option<f64>, Option<f64>) {
+        let (mut accepted, mut total, mut window_accepted, mut window_total) = (0, 0, 0, 0);
+        for player in self.players.iter() {
+            accepted += player.acceptance.accepted();
+            total += player.acceptance.total();
+            window_accepted += player.acceptance.window_accepted();
+            window_total += player.acceptance.window_len();
+        }
+        let rate = |a: usize, t: usize| (t > 0).then(|| a as f64 / t as f64);
+        (rate(accepted, total), rate(window_accepted, window_total))
+    }
+
+    /// Shows `viewport` and redraws everything that depends on it.
+    pub fn set_viewport(&mut self, viewport: Viewport) {
+        self.viewport = viewport;
+        self.line_cache.clear();
+        self.density_cache.clear();
+        self.redraw();
+    }
+
+    /// Changes the target and redraws its background and contours.
+    pub fn set_target(&mut self, mean: Point, stddev: Point) {
+        self.mean = mean;
+        self.stddev = stddev;
+        self.density_cache.clear();
+        self.fit_histograms();
+    }
+
+    pub fn set_show_target(&mut self, show: bool) {
+        self.show_target = show;
+        self.density_cache.clear();
+    }
+
     pub fn redraw(&mut self) {
         self.position_cache.clear();
         self.x_curve_cache.clear();
-        self.y_curve_cache.clear()
+        self.y_curve_cache.clear();
+        self.histogram_cache.clear();
+        self.heatmap_cache.clear();
+    }
+
+    pub fn set_target_kind(&mut self, target: Target) {
+        self.target = target;
+        self.density_cache.clear();
+        self.fit_histograms();
+    }
+
+    /// Applies the brush at `position`.
+    pub fn paint(&mut self, position: Point) {
+        let amount = if self.erasing {
+            -BRUSH_AMOUNT
+        } else {
+            BRUSH_AMOUNT
+        };
+        self.painted.brush(position, self.brush_radius, amount);
+        self.density_cache.clear();
+    }
+
+    pub fn smooth_painting(&mut self) {
+        self.painted.smooth();
+        self.density_cache.clear();
+    }
+
+    pub fn clear_painting(&mut self) {
+        self.painted.clear();
+        self.density_cache.clear();
+    }
+
+    fn target_density(&self, position: Point) -> f64 {
+        match self.target {
+            Target::Gaussian => gaussian::density_2d(
+                (self.mean.x as f64, self.mean.y as f64),
+                (self.stddev.x as f64, self.stddev.y as f64),
+                position.x as f64,
+                position.y as f64,
+            ),
+            Target::Painted => self.painted.density(position),
+        }
+    }
+
+    /// Corners of the region holding (nearly) all of the target's mass.
+    fn target_domain(&self) -> (Point, Point) {
+        match self.target {
+            Target::Gaussian => gaussian_domain(self.mean, self.stddev),
+            Target::Painted => (self.painted.min, self.painted.max),
+        }
     }
 
     fn x_value_at(&self, x: f64) -> f64 {
-        gaussian::distribution_density(self.mean.x as f64, self.stddev.x as f64, x)
+        match self.target {
+            Target::Gaussian => {
+                gaussian::distribution_density(self.mean.x as f64, self.stddev.x as f64, x)
+            }
+            Target::Painted => self.painted.marginal(x as f32, true),
+        }
     }
     fn y_value_at(&self, y: f64) -> f64 {
-        gaussian::distribution_density(self.mean.y as f64, self.stddev.y as f64, y)
+        match self.target {
+            Target::Gaussian => {
+                gaussian::distribution_density(self.mean.y as f64, self.stddev.y as f64, y)
+            }
+            Target::Painted => self.painted.marginal(y as f32, false),
+        }
     }
 }
 
-impl<Message> canvas::Program<Message> for Stage {
-    type State = ();
+impl canvas::Program<StageEvent> for Stage {
+    type State = Interaction;
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: canvas::Event,
+        bounds: iced::Rectangle,
+        cursor: canvas::Cursor,
+    ) -> (event::Status, Option<StageEvent>) {
+        // a drag keeps going when the cursor leaves the stage.
+        if let Interaction::Panning { start, viewport } = *state {
+            match event {
+                canvas::Event::Mouse(mouse::Event::CursorMoved { position }) => {
+                    let size = plot_area(bounds.size()).size();
+                    let moved = viewport.dragged(position - start, size);
+                    return (
+                        event::Status::Captured,
+                        Some(StageEvent::ViewportChanged(moved)),
+                    );
+                }
+                canvas::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                    *state = Interaction::None;
+                    return (event::Status::Captured, None);
+                }
+                _ => {}
+            }
+        }
+        if let Interaction::Painting = *state {
+            match event {
+                canvas::Event::Mouse(mouse::Event::CursorMoved { .. }) => {
+                    let size = plot_area(bounds.size()).size();
+                    let painted = plot_cursor(bounds, cursor).map(|position| {
+                        StageEvent::Painted(self.viewport.to_model(position, size))
+                    });
+                    return (event::Status::Captured, painted);
+                }
+                canvas::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                    *state = Interaction::None;
+                    return (event::Status::Captured, None);
+                }
+                _ => {}
+            }
+        }
+        if let Interaction::Placing { start, .. } = *state {
+            let size = plot_area(bounds.size()).size();
+            match event {
+                canvas::Event::Mouse(mouse::Event::CursorMoved { position }) => {
+                    *state = Interaction::Placing {
+                        start,
+                        end: plot_relative(bounds, position),
+                    };
+                    return (event::Status::Captured, None);
+                }
+                canvas::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                    let end = cursor
+                        .position()
+                        .map_or(start, |position| plot_relative(bounds, position));
+                    *state = Interaction::None;
+                    let to_model = |p: Point| self.viewport.to_model(p, size);
+                    let placed = if start.distance(end) < PLACE_DRAG {
+                        StageEvent::PlayerPlaced(to_model(start))
+                    } else {
+                        StageEvent::RegionPlaced(to_model(start), to_model(end))
+                    };
+                    return (event::Status::Captured, Some(placed));
+                }
+                _ => {}
+            }
+        }
+        let position = match plot_cursor(bounds, cursor) {
+            Some(position) => position,
+            None => return (event::Status::Ignored, None),
+        };
+        let size = plot_area(bounds.size()).size();
+        match event {
+            canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left))
+                if self.tool == Tool::Paint =>
+            {
+                *state = Interaction::Painting;
+                (
+                    event::Status::Captured,
+                    Some(StageEvent::Painted(self.viewport.to_model(position, size))),
+                )
+            }
+            canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left))
+                if self.tool == Tool::Place =>
+            {
+                *state = Interaction::Placing {
+                    start: position,
+                    end: position,
+                };
+                (event::Status::Captured, None)
+            }
+            canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                match self.player_at(position, size) {
+                    Some(index) => (
+                        event::Status::Captured,
+                        Some(StageEvent::PlayerClicked(index)),
+                    ),
+                    None => {
+                        *state = Interaction::Panning {
+                            start: cursor.position().unwrap_or(position),
+                            viewport: self.viewport,
+                        };
+                        (event::Status::Captured, None)
+                    }
+                }
+            }
+            canvas::Event::Mouse(mouse::Event::WheelScrolled { delta }) => {
+                let lines = match delta {
+                    mouse::ScrollDelta::Lines { y, .. } => y,
+                    mouse::ScrollDelta::Pixels { y, .. } => y / PIXELS_PER_LINE,
+                };
+                let anchor = self.viewport.to_model(position, size);
+                let zoomed = self.viewport.zoomed(ZOOM_STEP.powf(lines), anchor);
+                (
+                    event::Status::Captured,
+                    Some(StageEvent::ViewportChanged(zoomed)),
+                )
+            }
+            _ => (event::Status::Ignored, None),
+        }
+    }
+
+    fn mouse_interaction(
+        &self,
+        state: &Self::State,
+        bounds: iced::Rectangle,
+        cursor: canvas::Cursor,
+    ) -> mouse::Interaction {
+        match state {
+            Interaction::Panning { .. } => return mouse::Interaction::Grabbing,
+            Interaction::Placing { .. } | Interaction::Painting => {
+                return mouse::Interaction::Crosshair
+            }
+            Interaction::None => {}
+        }
+        let size = plot_area(bounds.size()).size();
+        match plot_cursor(bounds, cursor) {
+            Some(_) if self.tool != Tool::Select => mouse::Interaction::Crosshair,
+            Some(position) if self.player_at(position, size).is_some() => {
+                mouse::Interaction::Pointer
+            }
+            Some(_) => mouse::Interaction::Grab,
+            None => mouse::Interaction::default(),
+        }
+    }
 
     fn draw(
         &self,
-        _state: &Self::State,
+        state: &Self::State,
         _theme: &Theme,
         bounds: iced::Rectangle,
-        _cursor: canvas::Cursor,
+        cursor: canvas::Cursor,
     ) -> Vec<canvas::Geometry> {
+        let plot = plot_area(bounds.size());
+        let size = plot.size();
+        let view = self.viewport;
+        let scale = view.scale(size);
+        let (visible_min, visible_max) = view.visible(size);
+        // pixel height of a marginal density, measured from its axis.
+        let marginal = |density: f64| density as f32 * MARGINAL_SCALE * scale;
+        let curve_stroke = canvas::Stroke {
+            width: 5.0,
+            style: stroke::Style::Solid(Color::WHITE),
+            ..Stroke::default()
+        };
         let x_curve = self.x_curve_cache.draw(bounds.size(), |frame| {
-            //let divisor = 50.0 / self.mu as f32;
-            let divisor = 50.0 / 2.0;
-            let mut current_point = Point { x: 0.0, y: 0.0 };
-            for i in 0..100 {
-                let i = i as f32;
-                let next_point = Point {
-                    x: i * 10.0,
-                    y: self.x_value_at((i as f64) / (divisor)) as f32 * 100.0,
-                };
+            frame.with_clip(plot, |frame| {
+                let step = (visible_max.x - visible_min.x) / CURVE_POINTS as f32;
                 frame.stroke(
                     &canvas::Path::new(|path| {
-                        path.move_to(current_point);
-                        path.line_to(Point {
-                            x: i * 10.0,
-                            y: self.x_value_at((i as f64) / divisor) as f32 * 100.0,
-                        });
+                        for i in 0..=CURVE_POINTS {
+                            let x = visible_min.x + i as f32 * step;
+                            let point = Point {
+                                x: view.to_screen(Point::new(x, 0.0), size).x,
+                                y: size.height - marginal(self.x_value_at(x as f64)),
+                            };
+                            if i == 0 {
+                                path.move_to(point);
+                            } else {
+                                path.line_to(point);
+                            }
+                        }
                     }),
-                    canvas::Stroke {
-                        width: 5.0,
-                        style: stroke::Style::Solid(Color::WHITE),
-                        ..Stroke::default()
-                    },
+                    curve_stroke.clone(),
                 );
-                current_point = next_point
-            }
+            })
         });
         let y_curve = self.y_curve_cache.draw(bounds.size(), |frame| {
-            //let divisor = 50.0 / self.mu as f32;
-            let divisor = 50.0 / 2.0;
-            let mut current_point = Point { x: 0.0, y: 0.0 };
-            for i in 0..100 {
-                let i = i as f32;
-                let next_point = Point {
-                    y: i * 10.0,
-                    x: self.y_value_at((i as f64) / (divisor)) as f32 * 100.0,
-                };
+            frame.with_clip(plot, |frame| {
+                let step = (visible_max.y - visible_min.y) / CURVE_POINTS as f32;
                 frame.stroke(
                     &canvas::Path::new(|path| {
-                        path.move_to(current_point);
-                        path.line_to(Point {
-                            y: i * 10.0,
-                            x: self.y_value_at((i as f64) / divisor) as f32 * 100.0,
-                        });
+                        for i in 0..=CURVE_POINTS {
+                            let y = visible_min.y + i as f32 * step;
+                            let point = Point {
+                                x: marginal(self.y_value_at(y as f64)),
+                                y: view.to_screen(Point::new(0.0, y), size).y,
+                            };
+                            if i == 0 {
+                                path.move_to(point);
+                            } else {
+                                path.line_to(point);
+                            }
+                        }
                     }),
-                    canvas::Stroke {
-                        width: 5.0,
-                        style: stroke::Style::Solid(Color::WHITE),
-                        ..Stroke::default()
-                    },
+                    curve_stroke.clone(),
                 );
-                current_point = next_point
-            }
+            })
         });
-        let geom = self.line_cache.draw(bounds.size(), |frame| {
-            let current_point = Point { x: 0.0, y: 0.0 };
-            frame.stroke(
-                &canvas::Path::new(|path| {
-                    path.move_to(current_point);
-                    path.line_to(Point {
-                        x: bounds.width,
-                        y: 0.0,
-                    });
-                }),
-                canvas::Stroke {
-                    width: 5.0,
-                    style: stroke::Style::Solid(Color::WHITE),
-                    ..Stroke::default()
-                },
-            );
-            let current_point = Point {
-                x: bounds.width / 2.0,
-                y: 0.0,
+        let density = self.density_cache.draw(bounds.size(), |frame| {
+            frame.with_clip(plot, |frame| {
+                if !self.show_target || self.target == Target::Painted && self.painted.is_empty() {
+                    return;
+                }
+                // levels come from the whole target, not just the visible part.
+                let (domain_min, domain_max) = self.target_domain();
+                let domain = Grid::sample(
+                    |p| self.target_density(p),
+                    domain_min,
+                    domain_max,
+                    LEVEL_NODES,
+                    LEVEL_NODES,
+                );
+                let peak = domain.max();
+                if peak <= 0.0 {
+                    return;
+                }
+                let levels = contour::hpd_levels(&domain, &HPD_LEVELS);
+                let grid = Grid::sample(
+                    |p| self.target_density(p),
+                    visible_min,
+                    visible_max,
+                    DENSITY_CELLS + 1,
+                    DENSITY_CELLS + 1,
+                );
+                let cell = Size::new(
+                    size.width / DENSITY_CELLS as f32,
+                    size.height / DENSITY_CELLS as f32,
+                );
+                // grid coordinates to pixels; rows run bottom to top.
+                let to_screen = |p: Point| Point {
+                    x: p.x * cell.width,
+                    y: size.height - p.y * cell.height,
+                };
+                for j in 0..DENSITY_CELLS {
+                    for i in 0..DENSITY_CELLS {
+                        let value = (grid.value(i, j)
+                            + grid.value(i + 1, j)
+                            + grid.value(i, j + 1)
+                            + grid.value(i + 1, j + 1))
+                            / 4.0;
+                        let t = (value / peak) as f32;
+                        if t < 1e-3 {
+                            continue;
+                        }
+                        frame.fill_rectangle(
+                            to_screen(Point::new(i as f32, (j + 1) as f32)),
+                            cell,
+                            colormap::viridis(t.min(1.0), 0.35),
+                        );
+                    }
+                }
+                for (level, probability) in levels.iter().zip(HPD_LEVELS) {
+                    let segments = contour::marching_squares(&grid, *level);
+                    frame.stroke(
+                        &canvas::Path::new(|path| {
+                            for (a, b) in segments.iter() {
+                                path.move_to(to_screen(*a));
+                                path.line_to(to_screen(*b));
+                            }
+                        }),
+                        canvas::Stroke {
+                            width: 1.5,
+                            style: stroke::Style::Solid(Color::from_rgba(1.0, 1.0, 1.0, 0.7)),
+                            ..Stroke::default()
+                        },
+                    );
+                    // label each contour at its rightmost point.
+                    let rightmost = segments
+                        .iter()
+                        .flat_map(|(a, b)| [*a, *b])
+                        .max_by(|a, b| a.x.total_cmp(&b.x));
+                    if let Some(point) = rightmost {
+                        frame.fill_text(canvas::Text {
+                            content: format!("{:.0}%", probability * 100.0),
+                            position: to_screen(point) + Vector::new(4.0, 0.0),
+                            color: Color::from_rgba(1.0, 1.0, 1.0, 0.7),
+                            size: 12.0,
+                            vertical_alignment: alignment::Vertical::Center,
+                            ..canvas::Text::default()
+                        });
+                    }
+                }
+            })
+        });
+        let heatmap = self.heatmap_cache.draw(bounds.size(), |frame| {
+            frame.with_clip(plot, |frame| {
+                let current;
+                let grid = match self.heatmap_mode {
+                    HeatmapMode::Off => return,
+                    HeatmapMode::AllSamples => &self.heatmap,
+                    HeatmapMode::Current => {
+                        let mut grid = Histogram2d::new(
+                            (self.heatmap.x.min, self.heatmap.x.max),
+                            (self.heatmap.y.min, self.heatmap.y.max),
+                            (HEATMAP_BINS, HEATMAP_BINS),
+                        );
+                        for player in self.players.iter() {
+                            grid.add(player.current.x as f64, player.current.y as f64);
+                        }
+                        current = grid;
+                        &current
+                    }
+                };
+                let max = grid.max_count();
+                if max == 0 {
+                    return;
+                }
+                for j in 0..grid.y.bins() {
+                    for i in 0..grid.x.bins() {
+                        let count = grid.count(i, j);
+                        if count == 0 {
+                            continue;
+                        }
+                        let start =
+                            Point::new(grid.x.bin_start(i) as f32, grid.y.bin_start(j) as f32);
+                        let end = Point::new(
+                            grid.x.bin_start(i + 1) as f32,
+                            grid.y.bin_start(j + 1) as f32,
+                        );
+                        let cell = view.to_screen_rect(start, end, size);
+                        frame.fill_rectangle(
+                            cell.position(),
+                            cell.size(),
+                            colormap::viridis(count as f32 / max as f32, 0.8),
+                        );
+                    }
+                }
+            })
+        });
+        let histograms = self.histogram_cache.draw(bounds.size(), |frame| {
+            frame.with_clip(plot, |frame| {
+                // same scale as the analytic curves.
+                let color = Color::from_rgba8(0x12, 0x93, 0xD8, 0.5);
+                let hist = &self.x_histogram;
+                for bin in 0..hist.bins() {
+                    let left = view.to_screen(Point::new(hist.bin_start(bin) as f32, 0.0), size);
+                    let right =
+                        view.to_screen(Point::new(hist.bin_start(bin + 1) as f32, 0.0), size);
+                    let height = marginal(hist.density(bin));
+                    frame.fill_rectangle(
+                        Point {
+                            x: left.x,
+                            y: size.height - height,
+                        },
+                        iced::Size {
+                            width: right.x - left.x,
+                            height,
+                        },
+                        color,
+                    );
+                }
+                let hist = &self.y_histogram;
+                for bin in 0..hist.bins() {
+                    let bottom = view.to_screen(Point::new(0.0, hist.bin_start(bin) as f32), size);
+                    let top = view.to_screen(Point::new(0.0, hist.bin_start(bin + 1) as f32), size);
+                    frame.fill_rectangle(
+                        Point { x: 0.0, y: top.y },
+                        iced::Size {
+                            width: marginal(hist.density(bin)),
+                            height: bottom.y - top.y,
+                        },
+                        color,
+                    );
+                }
+            })
+        });
+        let axes = self.line_cache.draw(bounds.size(), |frame| {
+            let grid = canvas::Stroke {
+                width: 1.0,
+                style: stroke::Style::Solid(Color::from_rgb8(0x33, 0x33, 0x33)),
+                ..Stroke::default()
             };
+            let axis = canvas::Stroke {
+                width: 2.0,
+                style: stroke::Style::Solid(Color::WHITE),
+                ..Stroke::default()
+            };
+            let label = |content: String, position: Point, horizontal, vertical| canvas::Text {
+                content,
+                position,
+                color: Color::from_rgb8(0xaa, 0xaa, 0xaa),
+                size: 12.0,
+                horizontal_alignment: horizontal,
+                vertical_alignment: vertical,
+                ..canvas::Text::default()
+            };
+            let left = plot.x;
+            let bottom = plot.y + plot.height;
+            let x_count = (plot.width / TICK_SPACING) as usize;
+            let (x_ticks, x_step) = viewport::ticks(visible_min.x, visible_max.x, x_count);
+            for x in x_ticks {
+                let screen = left + view.to_screen(Point::new(x, 0.0), size).x;
+                frame.stroke(
+                    &canvas::Path::line(Point::new(screen, plot.y), Point::new(screen, bottom)),
+                    grid.clone(),
+                );
+                frame.stroke(
+                    &canvas::Path::line(
+                        Point::new(screen, bottom),
+                        Point::new(screen, bottom + TICK_LENGTH),
+                    ),
+                    axis.clone(),
+                );
+                frame.fill_text(label(
+                    viewport::tick_label(x, x_step),
+                    Point::new(screen, bottom + TICK_LENGTH + 2.0),
+                    alignment::Horizontal::Center,
+                    alignment::Vertical::Top,
+                ));
+            }
+            let y_count = (plot.height / TICK_SPACING) as usize;
+            let (y_ticks, y_step) = viewport::ticks(visible_min.y, visible_max.y, y_count);
+            for y in y_ticks {
+                let screen = plot.y + view.to_screen(Point::new(0.0, y), size).y;
+                frame.stroke(
+                    &canvas::Path::line(
+                        Point::new(left, screen),
+                        Point::new(left + plot.width, screen),
+                    ),
+                    grid.clone(),
+                );
+                frame.stroke(
+                    &canvas::Path::line(
+                        Point::new(left - TICK_LENGTH, screen),
+                        Point::new(left, screen),
+                    ),
+                    axis.clone(),
+                );
+                frame.fill_text(label(
+                    viewport::tick_label(y, y_step),
+                    Point::new(left - TICK_LENGTH - 2.0, screen),
+                    alignment::Horizontal::Right,
+                    alignment::Vertical::Center,
+                ));
+            }
             frame.stroke(
                 &canvas::Path::new(|path| {
-                    path.move_to(current_point);
-                    path.line_to(Point {
-                        x: bounds.width / 2.0,
-                        y: bounds.height,
-                    });
+                    path.move_to(Point::new(left, plot.y));
+                    path.line_to(Point::new(left, bottom));
+                    path.line_to(Point::new(left + plot.width, bottom));
                 }),
-                canvas::Stroke {
-                    width: 5.0,
-                    style: stroke::Style::Solid(Color::WHITE),
-                    ..Stroke::default()
-                },
-            )
+                axis,
+            );
+            frame.fill_text(label(
+                String::from("x"),
+                Point::new(left + plot.width / 2.0, bounds.height - 2.0),
+                alignment::Horizontal::Center,
+                alignment::Vertical::Bottom,
+            ));
+            frame.fill_text(label(
+                String::from("y"),
+                Point::new(2.0, plot.y + plot.height / 2.0),
+                alignment::Horizontal::Left,
+                alignment::Vertical::Center,
+            ));
         });
 
         let pos = self.position_cache.draw(bounds.size(), |frame| {
-            for player in self.players.iter() {
-                // let path: canvas::Path = canvas::Path::circle(player.candidate.position, 10.0);
-                // frame.fill(&path, Color::from_rgb8(0xe7, 0x6f, 0x51));
-                let path: canvas::Path = canvas::Path::circle(player.current, 5.0);
-                frame.fill(&path, Color::from_rgb8(0x12, 0x93, 0xD8));
-            }
+            frame.with_clip(plot, |frame| {
+                let to_screen = |position: Point| view.to_screen(position, size);
+                for curve in self.overlay.iter() {
+                    frame.stroke(
+                        &canvas::Path::new(|path| {
+                            for (i, point) in curve.iter().enumerate() {
+                                if i == 0 {
+                                    path.move_to(to_screen(*point));
+                                } else {
+                                    path.line_to(to_screen(*point));
+                                }
+                            }
+                        }),
+                        canvas::Stroke {
+                            width: 2.0,
+                            style: stroke::Style::Solid(Color::from_rgb8(0x2a, 0x9d, 0x8f)),
+                            ..Stroke::default()
+                        },
+                    );
+                }
+                for (start, end) in self.players.iter().filter_map(|player| player.line) {
+                    frame.stroke(
+                        &canvas::Path::line(to_screen(start), to_screen(end)),
+                        canvas::Stroke {
+                            width: 1.0,
+                            style: stroke::Style::Solid(Color::from_rgba8(0xe7, 0x6f, 0x51, 0.4)),
+                            ..Stroke::default()
+                        },
+                    );
+                }
+                for player in self.players.iter() {
+                    let len = player.trail.len() as f32;
+                    for (k, step) in player.trail.iter().enumerate() {
+                        // older steps fade out.
+                        let alpha = 0.7 * (k + 1) as f32 / len;
+                        let from = to_screen(step.from);
+                        if step.accepted() {
+                            frame.stroke(
+                                &canvas::Path::line(from, to_screen(step.to)),
+                                canvas::Stroke {
+                                    width: 1.5,
+                                    style: stroke::Style::Solid(Color::from_rgba8(
+                                        0x12, 0x93, 0xD8, alpha,
+                                    )),
+                                    ..Stroke::default()
+                                },
+                            );
+                        } else if let Some(proposal) = step.proposal {
+                            // a short tick pointing at the rejected candidate.
+                            let towards = to_screen(proposal) - from;
+                            let length = towards.x.hypot(towards.y).max(f32::EPSILON);
+                            let tick = towards * (length.min(REJECTED_TICK) / length);
+                            frame.stroke(
+                                &canvas::Path::line(from, from + tick),
+                                canvas::Stroke {
+                                    width: 1.5,
+                                    style: stroke::Style::Solid(Color::from_rgba8(
+                                        0xe6, 0x39, 0x46, alpha,
+                                    )),
+                                    ..Stroke::default()
+                                },
+                            );
+                        }
+                    }
+                }
+                for player in self.players.iter() {
+                    let path: canvas::Path =
+                        canvas::Path::circle(to_screen(player.current), 5.0 * player.weight.sqrt());
+                    frame.fill(&path, acceptance_color(player.acceptance.recent()));
+                }
+                for (i, index) in self.selected.iter().enumerate() {
+                    if let Some(player) = self.players.get(*index) {
+                        frame.stroke(
+                            &canvas::Path::circle(to_screen(player.current), 9.0),
+                            canvas::Stroke {
+                                width: 2.0,
+                                style: stroke::Style::Solid(SELECTION_COLORS[i]),
+                                ..Stroke::default()
+                            },
+                        );
+                    }
+                }
+                if let Some((index, explanation)) = &self.explanation {
+                    if self.selected.contains(index) {
+                        draw_explanation(frame, explanation, to_screen);
+                    }
+                }
+                if let Some(best) = self.best {
+                    frame.stroke(
+                        &canvas::Path::circle(to_screen(best), 9.0),
+                        canvas::Stroke {
+                            width: 3.0,
+                            style: stroke::Style::Solid(Color::from_rgb8(0xf4, 0xa2, 0x61)),
+                            ..Stroke::default()
+                        },
+                    );
+                }
+            })
+        });
+        // the inspector and placement region follow the cursor, so they are
+        // never cached.
+        let mut inspector = canvas::Frame::new(bounds.size());
+        if let Interaction::Placing { start, end } = *state {
+            inspector.with_clip(plot, |frame| {
+                let region = iced::Rectangle::new(
+                    Point::new(start.x.min(end.x), start.y.min(end.y)),
+                    Size::new((end.x - start.x).abs(), (end.y - start.y).abs()),
+                );
+                let path = canvas::Path::rectangle(region.position(), region.size());
+                frame.fill(&path, Color::from_rgba8(0x12, 0x93, 0xD8, 0.15));
+                frame.stroke(
+                    &path,
+                    canvas::Stroke {
+                        width: 1.5,
+                        style: stroke::Style::Solid(Color::from_rgb8(0x12, 0x93, 0xD8)),
+                        ..Stroke::default()
+                    },
+                );
+            });
+        }
+        if let Some(position) = plot_cursor(bounds, cursor).filter(|_| self.tool == Tool::Paint) {
+            inspector.with_clip(plot, |frame| {
+                frame.stroke(
+                    &canvas::Path::circle(position, self.brush_radius * scale),
+                    canvas::Stroke {
+                        width: 1.5,
+                        style: stroke::Style::Solid(if self.erasing {
+                            Color::from_rgb8(0xe6, 0x39, 0x46)
+                        } else {
+                            Color::WHITE
+                        }),
+                        ..Stroke::default()
+                    },
+                );
+            });
+        } else if let Some(index) =
+            plot_cursor(bounds, cursor).and_then(|position| self.player_at(position, size))
+        {
+            inspector.with_clip(plot, |frame| {
+                draw_inspector(frame, index, &self.players[index], |p| {
+                    view.to_screen(p, size)
+                });
+            });
+        }
+        vec![
+            density,
+            heatmap,
+            axes,
+            histograms,
+            pos,
+            x_curve,
+            y_curve,
+            inspector.into_geometry(),
+        ]
+    }
+}
+
+/// Players fade from blue to red as their recent acceptance rate drops towards
+/// zero, so stuck chains stand out.
+fn acceptance_color(rate: Option<f64>) -> Color {
+    let healthy = Color::from_rgb8(0x12, 0x93, 0xD8);
+    let stuck = Color::from_rgb8(0xe6, 0x39, 0x46);
+    match rate {
+        Some(rate) => {
+            let t = (rate / 0.2).min(1.0) as f32;
+            Color::from_rgb(
+                stuck.r + (healthy.r - stuck.r) * t,
+                stuck.g + (healthy.g - stuck.g) * t,
+                stuck.b + (healthy.b - stuck.b) * t,
+            )
+        }
+        None => healthy,
+    }
+}
+
+/// Corners of the region within `DOMAIN_REACH` std. devs. of `mean`.
+fn gaussian_domain(mean: Point, stddev: Point) -> (Point, Point) {
+    let reach = Vector::new(stddev.x * DOMAIN_REACH, stddev.y * DOMAIN_REACH);
+    (mean - reach, mean + reach)
+}
+
+/// Empty x, y and 2D histograms over the rectangle `domain`.
+fn histograms(domain: (Point, Point)) -> (Histogram, Histogram, Histogram2d) {
+    let (min, max) = domain;
+    let x_range = (min.x as f64, max.x as f64);
+    let y_range = (min.y as f64, max.y as f64);
+    (
+        Histogram::new(x_range.0, x_range.1, HISTOGRAM_BINS),
+        Histogram::new(y_range.0, y_range.1, HISTOGRAM_BINS),
+        Histogram2d::new(x_range, y_range, (HEATMAP_BINS, HEATMAP_BINS)),
+    )
+}
+
+/// Part of a canvas of `size` the viewport is drawn into, leaving margins for
+/// the axes.
+fn plot_area(size: Size) -> iced::Rectangle {
+    iced::Rectangle {
+        x: AXIS_MARGIN.0,
+        y: PLOT_PADDING,
+        width: (size.width - AXIS_MARGIN.0 - PLOT_PADDING).max(0.0),
+        height: (size.height - AXIS_MARGIN.1 - PLOT_PADDING).max(0.0),
+    }
+}
+
+/// Cursor position relative to the plot area, if it is over it.
+fn plot_cursor(bounds: iced::Rectangle, cursor: canvas::Cursor) -> Option<Point> {
+    let plot = plot_area(bounds.size());
+    cursor
+        .position_in(&bounds)
+        .filter(|position| plot.contains(*position))
+        .map(|position| position - (plot.position() - Point::ORIGIN))
+}
+
+/// Window position relative to the plot area, wherever it is.
+fn plot_relative(bounds: iced::Rectangle, position: Point) -> Point {
+    let plot = plot_area(bounds.size());
+    position - (bounds.position() - Point::ORIGIN) - (plot.position() - Point::ORIGIN)
+}
+
+/// Annotates a Metropolis step: the proposal, the densities at both ends, the
+/// acceptance probabilities, the uniform draw and what was decided.
+fn draw_explanation(
+    frame: &mut canvas::Frame,
+    explanation: &Explanation,
+    to_screen: impl Fn(Point) -> Point,
+) {
+    let highlight = Color::from_rgb8(0xf4, 0xa2, 0x61);
+    let current = to_screen(explanation.current);
+    let candidate = to_screen(explanation.candidate);
+    frame.stroke(
+        &canvas::Path::line(current, candidate),
+        canvas::Stroke {
+            width: 1.5,
+            style: stroke::Style::Solid(highlight),
+            ..Stroke::default()
+        },
+    );
+    frame.stroke(
+        &canvas::Path::circle(candidate, 6.0),
+        canvas::Stroke {
+            width: 2.0,
+            style: stroke::Style::Solid(highlight),
+            ..Stroke::default()
+        },
+    );
+    let (accept_x, accept_y) = explanation.accepted();
+    let verdict = |accepted: bool| if accepted { "accept" } else { "reject" };
+    let lines = [
+        format!(
+            "current   ({:.3}, {:.3})  f = {:.3} x {:.3}",
+            explanation.current.x,
+            explanation.current.y,
+            explanation.current_density.0,
+            explanation.current_density.1
+        ),
+        format!(
+            "candidate ({:.3}, {:.3})  f = {:.3} x {:.3}",
+            explanation.candidate.x,
+            explanation.candidate.y,
+            explanation.candidate_density.0,
+            explanation.candidate_density.1
+        ),
+        format!(
+            "a = min(1, f(cand) / f(cur)) = ({:.3}, {:.3})",
+            explanation.acceptance.0, explanation.acceptance.1
+        ),
+        format!("u = {:.3}", explanation.uniform),
+        format!(
+            "x: {} (a {} u)   y: {} (a {} u)",
+            verdict(accept_x),
+            if accept_x { ">" } else { "<=" },
+            verdict(accept_y),
+            if accept_y { ">" } else { "<=" }
+        ),
+    ];
+    let lines = lines
+        .into_iter()
+        .enumerate()
+        .map(|(i, line)| (line, if i == 4 { highlight } else { Color::WHITE }))
+        .collect();
+    text_box(frame, candidate, EXPLANATION_WIDTH, lines);
+}
+
+/// Lines of text on a dark box beside `anchor`, kept inside the frame where
+/// possible.
+fn text_box(frame: &mut canvas::Frame, anchor: Point, width: f32, lines: Vec<(String, Color)>) {
+    let size = Size::new(width, TEXT_LINE * lines.len() as f32 + 8.0);
+    let position = Point {
+        x: (anchor.x + 12.0).min(frame.width() - size.width).max(0.0),
+        y: (anchor.y - size.height / 2.0)
+            .min(frame.height() - size.height)
+            .max(0.0),
+    };
+    frame.fill_rectangle(position, size, Color::from_rgba(0.0, 0.0, 0.0, 0.75));
+    for (i, (content, color)) in lines.into_iter().enumerate() {
+        frame.fill_text(canvas::Text {
+            content,
+            position: position + Vector::new(6.0, 4.0 + TEXT_LINE * i as f32),
+            color,
+            size: 13.0,
+            ..canvas::Text::default()
         });
-        vec![geom, pos, x_curve, y_curve]
     }
 }
+
+/// Highlights the stored trajectory of the player under the cursor and lists
+/// its state next to it.
+fn draw_inspector(
+    frame: &mut canvas::Frame,
+    index: usize,
+    player: &Player,
+    to_screen: impl Fn(Point) -> Point,
+) {
+    let highlight = Color::from_rgb8(0xff, 0xd1, 0x66);
+    frame.stroke(
+        &canvas::Path::new(|path| {
+            for (i, sample) in player.history.samples().enumerate() {
+                if i == 0 {
+                    path.move_to(to_screen(sample.position));
+                } else {
+                    path.line_to(to_screen(sample.position));
+                }
+            }
+            path.line_to(to_screen(player.current));
+        }),
+        canvas::Stroke {
+            width: 1.5,
+            style: stroke::Style::Solid(Color {
+                a: 0.8,
+                ..highlight
+            }),
+            ..Stroke::default()
+        },
+    );
+    let current = to_screen(player.current);
+    frame.stroke(
+        &canvas::Path::circle(current, 8.0),
+        canvas::Stroke {
+            width: 2.0,
+            style: stroke::Style::Solid(highlight),
+            ..Stroke::default()
+        },
+    );
+    let percent = |rate: Option<f64>| match rate {
+        Some(rate) => format!("{:.1}%", rate * 100.0),
+        None => String::from("-"),
+    };
+    let log_density = match player.log_density {
+        Some(log_density) => format!("{:.3}", log_density),
+        None => String::from("-"),
+    };
+    let lines = vec![
+        (format!("chain {}", index), highlight),
+        (
+            format!(
+                "position ({:.3}, {:.3})",
+                player.current.x, player.current.y
+            ),
+            Color::WHITE,
+        ),
+        (format!("log density {}", log_density), Color::WHITE),
+        (
+            format!(
+                "acceptance {} (recent {})",
+                percent(player.acceptance.overall()),
+                percent(player.acceptance.recent())
+            ),
+            Color::WHITE,
+        ),
+        (format!("steps {}", player.history.steps()), Color::WHITE),
+    ];
+    text_box(frame, current, INSPECTOR_WIDTH, lines);
+}
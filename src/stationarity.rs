@@ -0,0 +1,136 @@
+/**
+ * Classical single-chain stationarity diagnostics: Geweke's z-score comparing
+ * the start and the end of a chain, and the Heidelberger-Welch stationarity
+ * (Cramer-von Mises on the cumulative sum bridge) and half-width tests. The
+ * spectral density at zero is estimated as var * n / ESS.
+ */
+use crate::diagnostics;
+
+// 5% critical value of the Cramer-von Mises statistic of a Brownian bridge.
+const CVM_CRITICAL: f64 = 0.461;
+// z value of the 95% interval used by the half-width test.
+const Z_95: f64 = 1.96;
+
+fn mean(x: &[f64]) -> f64 {
+    x.iter().sum::<f64>() / x.len() as f64
+}
+
+/// Spectral density at frequency zero, i.e. n times the variance of the mean.
+pub fn spectrum0(x: &[f64]) -> Option<f64> {
+    let n = x.len() as f64;
+    let m = mean(x);
+    let var = x.iter().map(|v| (v - m) * (v - m)).sum::<f64>() / (n - 1.0);
+    let ess = diagnostics::ess(&[x.to_vec()])?;
+    Some(var * n / ess)
+}
+
+/// Geweke z-score of the mean of the first `first` fraction of the chain
+/// against the mean of the last `last` fraction.
+pub fn geweke(x: &[f64], first: f64, last: f64) -> Option<f64> {
+    let n = x.len();
+    let a = &x[..(n as f64 * first) as usize];
+    let b = &x[n - (n as f64 * last) as usize..];
+    if a.len() < 4 || b.len() < 4 {
+        return None;
+    }
+    let var_a = spectrum0(a)? / a.len() as f64;
+    let var_b = spectrum0(b)? / b.len() as f64;
+    Some((mean(a) - mean(b)) / (var_a + var_b).sqrt())
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct HeidelbergerWelch {
+    pub stationary: bool,
+    // number of initial draws discarded before the chain looked stationary.
+    pub start: usize,
+    pub cvm: f64,
+    pub mean: f64,
+    pub halfwidth: f64,
+    pub halfwidth_passed: bool,
+}
+
+fn cramer_von_mises(x: &[f64], s0: f64) -> f64 {
+    let n = x.len() as f64;
+    let m = mean(x);
+    let mut cumulative = 0.0;
+    let mut sum = 0.0;
+    for v in x.iter() {
+        cumulative += v - m;
+        let bridge = cumulative / (n * s0).sqrt();
+        sum += bridge * bridge;
+    }
+    sum / n
+}
+
+/// Heidelberger-Welch: drop 10%, 20%, ... up to half of the chain until the
+/// remainder passes the stationarity test, then check that the 95% interval
+/// half-width of its mean is below `eps` relative to the mean. As in coda, the
+/// spectral density for the bridge comes from the second half of the chain,
+/// so an initial transient does not inflate it.
+pub fn heidelberger_welch(x: &[f64], eps: f64) -> Option<HeidelbergerWelch> {
+    let n = x.len();
+    let s0 = spectrum0(&x[n / 2..])?;
+    let mut result = None;
+    for tenth in 0..=5 {
+        let start = n * tenth / 10;
+        let rest = &x[start..];
+        if rest.len() < 8 {
+            break;
+        }
+        let cvm = cramer_von_mises(rest, s0);
+        let stationary = cvm < CVM_CRITICAL;
+        let m = mean(rest);
+        let halfwidth = Z_95 * (spectrum0(rest)? / rest.len() as f64).sqrt();
+        result = Some(HeidelbergerWelch {
+            stationary,
+            start,
+            cvm,
+            mean: m,
+            halfwidth,
+            halfwidth_passed: stationary && halfwidth < eps * m.abs(),
+        });
+        if stationary {
+            break;
+        }
+    }
+    result
+}
+
+/// Geweke and Heidelberger-Welch results for both coordinates of a chain.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChainReport {
+    pub geweke: (Option<f64>, Option<f64>),
+    pub heidelberger_welch: (Option<HeidelbergerWelch>, Option<HeidelbergerWelch>),
+}
+
+impl ChainReport {
+    pub fn geweke_passed(&self) -> bool {
+        let ok = |z: Option<f64>| z.map_or(true, |z| z.abs() < Z_95);
+        ok(self.geweke.0) && ok(self.geweke.1)
+    }
+
+    pub fn stationary(&self) -> bool {
+        let ok = |hw: Option<HeidelbergerWelch>| hw.map_or(true, |hw| hw.stationary);
+        ok(self.heidelberger_welch.0) && ok(self.heidelberger_welch.1)
+    }
+}
+
+pub fn report(xs: &[f64], ys: &[f64]) -> ChainReport {
+    ChainReport {
+        geweke: (geweke(xs, 0.1, 0.5), geweke(ys, 0.1, 0.5)),
+        heidelberger_welch: (heidelberger_welch(xs, 0.1), heidelberger_welch(ys, 0.1)),
+    }
+}
+
+/// Burn-in (in draws) that makes every stationary chain pass: the largest
+/// Heidelberger-Welch start over chains and coordinates.
+pub fn suggest_burn_in(reports: &[ChainReport]) -> usize {
+    reports
+        .iter()
+        .flat_map(|report| [report.heidelberger_welch.0, report.heidelberger_welch.1])
+        .flatten()
+        .filter(|hw| hw.stationary)
+        .map(|hw| hw.start)
+        .max()
+        .unwrap_or(0)
+}
@@ -0,0 +1,139 @@
+/**
+ * Mapping between model coordinates and canvas pixels.
+ */
+use iced::{Point, Rectangle, Size, Vector};
+
+// model-space region shown before the user changes the view.
+pub const DEFAULT_MIN: Point = Point { x: 0.0, y: 0.0 };
+pub const DEFAULT_MAX: Point = Point { x: 4.0, y: 4.0 };
+// limits on the width of the shown region so zooming cannot degenerate.
+const MIN_EXTENT: f32 = 1e-3;
+const MAX_EXTENT: f32 = 1e4;
+
+/// The region of model space shown on a canvas. It is fitted into the canvas
+/// bounds with the same scale on both axes, centred, and with y pointing up;
+/// the axis with slack shows a little more than `min..max`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self::new(DEFAULT_MIN, DEFAULT_MAX)
+    }
+}
+
+impl Viewport {
+    pub fn new(min: Point, max: Point) -> Self {
+        Self { min, max }
+    }
+
+    pub fn center(&self) -> Point {
+        Point {
+            x: (self.min.x + self.max.x) / 2.0,
+            y: (self.min.y + self.max.y) / 2.0,
+        }
+    }
+
+    /// Pixels per model unit when drawn into a canvas of `size`.
+    pub fn scale(&self, size: Size) -> f32 {
+        let scale =
+            (size.width / (self.max.x - self.min.x)).min(size.height / (self.max.y - self.min.y));
+        scale.max(f32::EPSILON)
+    }
+
+    /// Bottom-left and top-right model coordinates of the whole canvas.
+    pub fn visible(&self, size: Size) -> (Point, Point) {
+        let corner = self.to_model(Point::new(0.0, size.height), size);
+        let opposite = self.to_model(Point::new(size.width, 0.0), size);
+        (corner, opposite)
+    }
+
+    pub fn to_screen(&self, position: Point, size: Size) -> Point {
+        let scale = self.scale(size);
+        let center = self.center();
+        Point {
+            x: size.width / 2.0 + (position.x - center.x) * scale,
+            y: size.height / 2.0 - (position.y - center.y) * scale,
+        }
+    }
+
+    pub fn to_model(&self, position: Point, size: Size) -> Point {
+        let scale = self.scale(size);
+        let center = self.center();
+        Point {
+            x: center.x + (position.x - size.width / 2.0) / scale,
+            y: center.y - (position.y - size.height / 2.0) / scale,
+        }
+    }
+
+    /// Screen rectangle covering the model rectangle with corners `a` and `b`.
+    pub fn to_screen_rect(&self, a: Point, b: Point, size: Size) -> Rectangle {
+        let a = self.to_screen(a, size);
+        let b = self.to_screen(b, size);
+        Rectangle {
+            x: a.x.min(b.x),
+            y: a.y.min(b.y),
+            width: (a.x - b.x).abs(),
+            height: (a.y - b.y).abs(),
+        }
+    }
+
+    /// Viewport magnified by `factor` around the model point `anchor`, which
+    /// stays at the same place on screen. Zooming past the extent limits
+    /// leaves the viewport unchanged.
+    pub fn zoomed(&self, factor: f32, anchor: Point) -> Self {
+        let width = (self.max.x - self.min.x) / factor;
+        if !(MIN_EXTENT..=MAX_EXTENT).contains(&width) {
+            return *self;
+        }
+        let scale = |p: Point| Point {
+            x: anchor.x + (p.x - anchor.x) / factor,
+            y: anchor.y + (p.y - anchor.y) / factor,
+        };
+        Self::new(scale(self.min), scale(self.max))
+    }
+
+    /// Viewport moved so the content follows a drag of `delta` pixels on a
+    /// canvas of `size`.
+    pub fn dragged(&self, delta: Vector, size: Size) -> Self {
+        let scale = self.scale(size);
+        let shift = Vector::new(-delta.x / scale, delta.y / scale);
+        Self::new(self.min + shift, self.max + shift)
+    }
+}
+
+/// Evenly spaced round values (steps of 1, 2 or 5 times a power of ten)
+/// inside `min..=max`, roughly `count` of them, and the step between them.
+pub fn ticks(min: f32, max: f32, count: usize) -> (Vec<f32>, f32) {
+    let raw = (max - min) / count.max(1) as f32;
+    if !(raw > 0.0 && raw.is_finite()) {
+        return (Vec::new(), 0.0);
+    }
+    let magnitude = 10f32.powf(raw.log10().floor());
+    let step = magnitude
+        * match raw / magnitude {
+            r if r < 1.5 => 1.0,
+            r if r < 3.0 => 2.0,
+            r if r < 7.0 => 5.0,
+            _ => 10.0,
+        };
+    let first = (min / step).ceil() as i64;
+    let last = (max / step).floor() as i64;
+    let ticks = (first..=last).map(|i| i as f32 * step).collect();
+    (ticks, step)
+}
+
+/// Tick value printed with just enough decimals to tell neighbours apart.
+pub fn tick_label(value: f32, step: f32) -> String {
+    let decimals = (-step.log10().floor()).max(0.0) as usize;
+    // avoid printing "-0.0" for ticks that land on zero.
+    let value = if value.abs() < step * 1e-3 {
+        0.0
+    } else {
+        value
+    };
+    format!("{:.*}", decimals, value)
+}